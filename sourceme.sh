@@ -26,12 +26,89 @@ export TXFLAG=($NODE --chain-id $CHAIN_ID --gas-prices 0.025ujunox --gas auto --
 export CODE_ID="3420"
 
 export VAULT_CONTRACT="juno1cx3rj8qpxtzd8efqgjfxd2xjq6d0j5te3y7amzurp8upgwnyk43q44zljq"
-export INIT='{"cw20_addr":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"}'
-export CREATE_VAULT='{"create_vault":[]}'
-export WITHDRAW='{"withdraw":{"amount":"300"}}'
-export QUERY_VAULT='{"get_vault":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export INIT='{"tokens":[{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},{"native":"ujunox"}],"lock_duration":{"time":60}}'
+export UPDATE_CONFIG='{"update_config":{"lock_duration":{"time":86400}}}'
+export UNSET_UNBONDING_PERIOD='{"update_config":{"unset":["unbonding_period"]}}'
+export PROPOSE_NEW_ADMIN='{"propose_new_admin":{"new_admin":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export ACCEPT_ADMIN='{"accept_admin":{}}'
+export RENOUNCE_ADMIN='{"renounce_admin":{}}'
+export SET_PAUSER='{"set_pauser":{"pauser":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export PAUSE='{"pause":{"deposits":true,"withdrawals":true,"vault_creation":true,"until":{"at_time":"1700000000000000000"}}}'
+export UNPAUSE='{"unpause":{}}'
+export UPDATE_WIND_DOWN_CONFIG='{"update_config":{"wind_down_delay":{"time":604800},"max_pause_duration":{"time":2592000}}}'
+export START_WIND_DOWN='{"start_wind_down":{}}'
+export CANCEL_WIND_DOWN='{"cancel_wind_down":{}}'
+export EMERGENCY_EXIT='{"emergency_exit":{}}'
+export ADD_TOKEN='{"add_token":{"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"}}}'
+export REMOVE_TOKEN='{"remove_token":{"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"}}}'
+export ADD_COLLECTION='{"add_collection":{"collection":"juno1..."}}'
+export REMOVE_COLLECTION='{"remove_collection":{"collection":"juno1..."}}'
+export MIGRATE='{}'
+export CREATE_VAULT='{"create_vault":{}}'
+export CREATE_LABELED_VAULT='{"create_vault":{"label":"tax","lock_duration":{"time":86400}}}'
+export WITHDRAW='{"withdraw":{"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"300"}}'
+export UNBOND='{"unbond":{"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"300"}}'
+export DEPOSIT='{"deposit":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export WITHDRAW_NFT='{"withdraw_nft":{"collection":"juno1...","token_id":"1"}}'
+export PROPOSE_VAULT_TRANSFER='{"propose_vault_transfer":{"vault_id":0,"new_owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","expiry":{"at_time":"1700000000000000000"}}}'
+export ACCEPT_VAULT_TRANSFER='{"accept_vault_transfer":{}}'
+export CANCEL_VAULT_TRANSFER='{"cancel_vault_transfer":{"vault_id":0}}'
+export SET_DEPOSIT_POLICY='{"set_deposit_policy":{"vault_id":0,"policy":"allowlist"}}'
+export UPDATE_DEPOSITORS='{"update_depositors":{"vault_id":0,"add":["juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"]}}'
+export INCREASE_VAULT_ALLOWANCE='{"increase_vault_allowance":{"vault_id":0,"spender":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"300"}}'
+export DECREASE_VAULT_ALLOWANCE='{"decrease_vault_allowance":{"vault_id":0,"spender":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"100"}}'
+export WITHDRAW_FROM='{"withdraw_from":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0,"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"100"}}'
+export SET_GUARDIANS='{"set_guardians":{"vault_id":0,"guardians":["juno1...","juno1..."],"threshold":2,"delay":{"time":259200}}}'
+export INITIATE_RECOVERY='{"initiate_recovery":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0,"new_owner":"juno1..."}}'
+export APPROVE_RECOVERY='{"approve_recovery":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export CANCEL_RECOVERY='{"cancel_recovery":{"vault_id":0}}'
+export EXECUTE_RECOVERY='{"execute_recovery":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export SET_BENEFICIARIES='{"set_beneficiaries":{"vault_id":0,"beneficiaries":[{"addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","share":"1"}],"inactivity_period":{"time":31536000}}}'
+export HEARTBEAT='{"heartbeat":{}}'
+export CLAIM_INHERITANCE='{"claim_inheritance":{"owner":"juno1...","vault_id":0,"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"}}}'
+export CREATE_MULTISIG_VAULT='{"create_multisig_vault":{"label":"treasury","members":[{"addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","weight":1},{"addr":"juno1...","weight":1}],"threshold":2,"max_voting_period":{"time":604800}}}'
+export PROPOSE_WITHDRAW='{"propose":{"vault_id":0,"title":"rent","description":"pay rent","action":{"withdraw":{"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"100","recipient":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}}}'
+export VOTE='{"vote":{"proposal_id":1,"vote":"yes"}}'
+export EXECUTE_PROPOSAL='{"execute":{"proposal_id":1}}'
+export CLOSE_PROPOSAL='{"close":{"proposal_id":1}}'
+export CREATE_POOLED_VAULT='{"create_pooled_vault":{"label":"pool","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"}}}'
+export REDEEM='{"redeem":{"vault_id":1,"shares":"100000000"}}'
+export CLAIM='{"claim":{}}'
+export CLAIM_REWARDS='{"claim_rewards":{"vault_id":0}}'
+export EARLY_WITHDRAW='{"early_withdraw":{"token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"300"}}'
+export QUERY_EARLY_WITHDRAW_QUOTE='{"early_withdraw_quote":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"300"}}'
+export QUERY_PENDING_REWARDS='{"pending_rewards":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export QUERY_CLAIMS='{"claims":{"address":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","start_after":null,"limit":10}}'
+export QUERY_BALANCES='{"balances":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export QUERY_CONFIG='{"config":{}}'
+export QUERY_PAUSE_STATUS='{"pause_status":{}}'
+export QUERY_WIND_DOWN_STATUS='{"wind_down_status":{}}'
+export QUERY_TOKENS='{"tokens":{}}'
+export QUERY_COLLECTIONS='{"collections":{}}'
+export QUERY_VAULT='{"get_vault":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"}}}'
+export QUERY_LEDGER_ENTRIES='{"ledger_entries":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"start_after":null,"limit":10}}'
+export QUERY_DEPOSIT_BY_REFERENCE='{"deposit_by_reference":{"reference":"inv-42"}}'
+export QUERY_DEPOSITS_BY_DEPOSITOR='{"deposits_by_depositor":{"depositor":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","start_after":null,"limit":10}}'
+export QUERY_NFTS='{"nfts":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","start_after":null,"limit":10}}'
+export QUERY_VAULT_TRANSFER='{"vault_transfer":{"vault_owner_addr":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export QUERY_VAULTS_BY_OWNER='{"vaults_by_owner":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export QUERY_DEPOSIT_POLICY='{"deposit_policy":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export QUERY_ALLOWANCES='{"allowances":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export QUERY_RECOVERY='{"recovery":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export QUERY_INHERITANCE='{"inheritance":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","vault_id":0}}'
+export QUERY_LAST_ACTIVITY='{"last_activity":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export QUERY_PROPOSAL='{"proposal":{"proposal_id":1}}'
+export QUERY_LIST_PROPOSALS='{"list_proposals":{"start_after":null,"limit":10}}'
+export QUERY_TOTAL_ASSETS='{"total_assets":{"vault_id":1}}'
+export QUERY_PREVIEW_REDEEM='{"preview_redeem":{"vault_id":1,"shares":"100000000"}}'
+export QUERY_POOL_SHARE='{"pool_share":{"vault_id":1,"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
+export QUERY_VOTERS='{"voters":{"vault_id":0}}'
+export QUERY_LIST_VAULTS='{"list_vaults":{"start_after":null,"limit":10}}'
+export QUERY_WITHDRAWABLE_AMOUNT='{"withdrawable_amount":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"at_time":null,"limit":10}}'
+export QUERY_SIMULATE_WITHDRAW='{"simulate_withdraw":{"owner":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l","token":{"cw20":"juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"},"amount":"100"}}'
 
 export COIN_CONTRACT="juno1ka5p7mm8rfat7zs89xeegxyu9kxtljszckkdqfgv4e5x023c3hws7wjxaw"
 export SEND_TO_VAULT='{"send":{"contract":"juno1cx3rj8qpxtzd8efqgjfxd2xjq6d0j5te3y7amzurp8upgwnyk43q44zljq", "amount":"200", "msg":"eyJ2YXVsdF9vd25lcl9hZGRyIjoianVubzFzdWxtNGdhOGpnZDczenM1cTl3c3Vtc3p1N25zNm5rZ3h4dmYzbCJ9"}}'
+export FUND_REWARDS='{"send":{"contract":"juno1cx3rj8qpxtzd8efqgjfxd2xjq6d0j5te3y7amzurp8upgwnyk43q44zljq", "amount":"1000", "msg":"eyJmdW5kX3Jld2FyZHMiOnsic3Rha2VkX3Rva2VuIjp7ImN3MjAiOiJqdW5vMWthNXA3bW04cmZhdDd6czg5eGVlZ3h5dTlreHRsanN6Y2trZHFmZ3Y0ZTV4MDIzYzNod3M3d2p4YXcifX19"}}'
 export QUERY_OWNER_BALANCE='{"balance":{"address":"juno1sulm4ga8jgd73zs5q9wsumszu7ns6nkgxxvf3l"}}'
 export QUERY_TOKEN_INFO='{"token_info":{}}'
@@ -1,24 +1,52 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint64, Uint128, Timestamp,
+    from_json, to_json_binary, Api, Binary, Addr, BlockInfo, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo,
+    Order, Response, StdError, StdResult, Storage, Timestamp, Uint64, Uint128, WasmMsg,
 };
+use cw_storage_plus::Bound;
+use std::cmp::Ordering;
 use cw2::set_contract_version;
+use cw_utils::Expiration;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, VaultResponse, QueryMsg, ReceiveMsg};
-use crate::state::{Config, Vault, CONFIG, VAULTS, Ledger};
-use cw20::{Cw20Contract, Cw20ExecuteMsg};
+use crate::msg::{
+    AllowanceId, AllowanceInfo, AllowancesResponse, Asset, CollectionsResponse, AssetsResponse, BalancesResponse, BeneficiaryInfo, ConfigField, ConfigResponse, DepositInfo, DepositPolicyResponse, DepositsResponse, PauseStatusResponse, WindDownStatusResponse, InheritanceResponse,
+    LastActivityResponse, RecoveryResponse, ClaimInfo, ClaimsResponse, Cw721ExecuteMsg, Cw721ReceiveMsg, EarlyWithdrawQuoteResponse,
+    ExecuteMsg, InstantiateMsg, LedgerEntriesResponse, LedgerEntry, LedgerWithdrawal, ListVaultsResponse,
+    MigrateMsg, NftId, NftInfo, NftsResponse, PendingRewardsResponse, PoolShareResponse, ProposalListResponse, ProposalResponse, ThresholdResponse, VaultRef,
+    VoterDetail, VoterListResponse, VaultTransferResponse, VaultsByOwnerResponse,
+    SharesResponse, SimulateWithdrawResponse, TokenBalance, TokensResponse, TotalAssetsResponse, UnlockInfo, VaultResponse, VaultSummary,
+    QueryMsg, ReceiveHookMsg, ReceiveMsg, UpdateConfigMsg, WithdrawableAmountResponse,
+};
+use crate::state::{
+    Activity, Allowance, AssetInfo, Ballot, Beneficiary, Claim, Deposit, DepositPolicy, DEPOSITORS, DEPOSITS, DEPOSIT_COUNT, DEPOSIT_POLICIES, REFERENCES, Guardians, Inheritance, Recovery, GUARDIANS, RECOVERIES, INHERITANCES, INHERITED, LAST_ACTIVITY, Config, EarlyWithdrawPenalty, LegacyConfig, Member, Multisig, MultisigAction,
+    NftLedger, Pause, PenaltyDestination, Pool, PoolShare, POOLS, POOL_SHARES, Proposal, Status, Vault, VaultBalance, VaultKey, VaultTransfer, Vote, Votes, ALLOWANCES,
+    BALANCES, BALLOTS, CLAIMS, CLAIM_COUNT, CONFIG, CONTRACT_ADDR, EXCLUDED_COLLECTED, INCOMING_TRANSFERS, LEDGERS, UNLOCK_HEIGHTS, UNLOCK_TIMES, UnlockIndex, LEGACY_CONFIG, LEGACY_VAULTS, MEMBERS, MULTISIGS,
+    NEXT_VAULT_ID, PAUSE, PAUSER, PENDING_ADMIN, WIND_DOWN, WindDown, NFTS, PENALTY_INDEX, PENDING_TRANSFERS, PROPOSALS, PROPOSAL_COUNT, REWARDS, REWARD_CHECKPOINTS, REWARD_INDEX, TOKENS, COLLECTIONS, TOTAL_COLLECTED, VAULTS, Ledger,
+};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::Duration;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-vault";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// pagination info for queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// length limits of memo and payment reference of a deposit in bytes
+const MAX_MEMO_LEN: usize = 256;
+const MAX_REFERENCE_LEN: usize = 64;
+
+// vault is moved to new owner in one transaction, so deposits, NFTs and claims moved with it are capped
+const MAX_MOVE_ENTRIES: usize = 100;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -27,15 +55,32 @@ pub fn instantiate(
     let owner = info.sender;
 
     let config = Config {
-        owner: owner.clone(),
-        cw20_addr: deps.api.addr_validate(msg.cw20_addr.as_str())?,
+        owner: Some(owner.clone()),
+        lock_duration: msg.lock_duration,
+        min_lock_duration: msg.min_lock_duration,
+        max_lock_duration: msg.max_lock_duration,
+        unbonding_period: msg.unbonding_period,
+        early_withdraw_penalty: msg.early_withdraw_penalty,
+        wind_down_delay: None,
+        max_pause_duration: None,
     };
+    // default lock duration should be within the bounds
+    if !is_lock_in_bounds(&config, &env.block, &config.lock_duration.after(&env.block)) {
+        return Err(ContractError::InvalidLockPeriod {});
+    }
+    validate_penalty(&config.early_withdraw_penalty)?;
     CONFIG.save(deps.storage, &config)?;
+    CONTRACT_ADDR.save(deps.storage, &env.contract.address)?;
+    for token in msg.tokens.iter() {
+        let token = validate_asset(deps.api, token)?;
+        TOKENS.save(deps.storage, &token.key(), &token)?;
+    }
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", owner)
-        .add_attribute("cw20_addr", msg.cw20_addr)
+        .add_attribute("tokens", msg.tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(","))
+        .add_attribute("lock_duration", msg.lock_duration.to_string())
     )
 }
 
@@ -47,485 +92,5877 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::CreateVault{} => {
-            // check if vault exists
-            match VAULTS.load(deps.storage, info.sender.clone()) {
-                Ok(_) => return Ok(Response::new()
-                    .add_attribute("method", "execute_create_vault")
-                    .add_attribute("msg", "vault already exists")
-                ),
-                Err(_) => ()
-            };
-
-            let vault = Vault {
-                owner_addr: Addr::unchecked(info.sender.clone()),
-                collected: Uint128::new(0),
-                ledger_list: vec![],
-            };
-            VAULTS.save(deps.storage, info.sender.clone(), &vault)?;
-            Ok(Response::new()
-                .add_attribute("method", "execute_create_vault")
-                .add_attribute("owner", info.sender.clone())
-            )
-        }
-        ExecuteMsg::Withdraw { amount } => {
-            let mut vault = VAULTS.load(deps.storage, info.sender.clone())?;
-            if amount > vault.collected {
-                return get_withdraw_fail_msg(vault.collected);
-            }
-            let ledgers = &mut vault.ledger_list;
-            let cur_time = env.block.time.seconds();
-            let mut amount_sum = Uint128::new(0);
-
-            // iterate and withdraw coin from ledger
-            for l in ledgers.iter_mut() {
-                // withdraw within 1 minute of deposit fails
-                if cur_time - l.receive_time.seconds() <= 60 {
-                    break;
-                }
-                if amount_sum + l.coin_amount >= amount {
-                    l.coin_amount -= amount - amount_sum;
-                    amount_sum = amount;
-                    break;
-                }
-                amount_sum += l.coin_amount;
-                l.coin_amount = Uint128::new(0);
-            }
+        ExecuteMsg::CreateVault { label, lock_duration } => execute_create_vault(deps, env, info, label, lock_duration),
+        ExecuteMsg::Withdraw { vault_id, token, amount } => execute_withdraw(deps, env, info, vault_id, token, amount),
+        ExecuteMsg::Unbond { vault_id, token, amount } => execute_unbond(deps, env, info, vault_id, token, amount),
+        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::ClaimRewards { vault_id } => execute_claim_rewards(deps, env, info, vault_id),
+        ExecuteMsg::EarlyWithdraw { vault_id, token, amount } => {
+            execute_early_withdraw(deps, env, info, vault_id, token, amount)
+        }
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::Deposit(msg) => execute_deposit(deps, env, info, msg),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::WithdrawNft { vault_id, collection, token_id } => {
+            execute_withdraw_nft(deps, env, info, vault_id, collection, token_id)
+        }
+        ExecuteMsg::ProposeVaultTransfer { vault_id, new_owner, expiry, merge } => {
+            execute_propose_vault_transfer(deps, env, info, vault_id, new_owner, expiry, merge)
+        }
+        ExecuteMsg::AcceptVaultTransfer { into_vault_id } => execute_accept_vault_transfer(deps, env, info, into_vault_id),
+        ExecuteMsg::CancelVaultTransfer { vault_id } => execute_cancel_vault_transfer(deps, env, info, vault_id),
+        ExecuteMsg::SetDepositPolicy { vault_id, policy } => {
+            execute_set_deposit_policy(deps, env, info, vault_id, policy)
+        }
+        ExecuteMsg::UpdateDepositors { vault_id, add, remove } => {
+            execute_update_depositors(deps, env, info, vault_id, add, remove)
+        }
+        ExecuteMsg::IncreaseVaultAllowance { vault_id, spender, token, amount, expires } => {
+            execute_increase_vault_allowance(deps, env, info, vault_id, spender, token, amount, expires)
+        }
+        ExecuteMsg::DecreaseVaultAllowance { vault_id, spender, token, amount, expires } => {
+            execute_decrease_vault_allowance(deps, env, info, vault_id, spender, token, amount, expires)
+        }
+        ExecuteMsg::WithdrawFrom { owner, vault_id, token, amount, recipient } => {
+            execute_withdraw_from(deps, env, info, owner, vault_id, token, amount, recipient)
+        }
+        ExecuteMsg::SetGuardians { vault_id, guardians, threshold, delay } => {
+            execute_set_guardians(deps, env, info, vault_id, guardians, threshold, delay)
+        }
+        ExecuteMsg::InitiateRecovery { owner, vault_id, new_owner } => {
+            execute_initiate_recovery(deps, env, info, owner, vault_id, new_owner)
+        }
+        ExecuteMsg::ApproveRecovery { owner, vault_id } => execute_approve_recovery(deps, env, info, owner, vault_id),
+        ExecuteMsg::CancelRecovery { vault_id } => execute_cancel_recovery(deps, env, info, vault_id),
+        ExecuteMsg::ExecuteRecovery { owner, vault_id } => execute_recovery(deps, env, owner, vault_id),
+        ExecuteMsg::SetBeneficiaries { vault_id, beneficiaries, inactivity_period } => {
+            execute_set_beneficiaries(deps, env, info, vault_id, beneficiaries, inactivity_period)
+        }
+        ExecuteMsg::Heartbeat {} => execute_heartbeat(deps, env, info),
+        ExecuteMsg::ClaimInheritance { owner, vault_id, token } => {
+            execute_claim_inheritance(deps, env, info, owner, vault_id, token)
+        }
+        ExecuteMsg::CreateMultisigVault { label, lock_duration, members, threshold, max_voting_period } => {
+            execute_create_multisig_vault(deps, env, label, lock_duration, members, threshold, max_voting_period)
+        }
+        ExecuteMsg::CreatePooledVault { label, token, lock_duration } => {
+            execute_create_pooled_vault(deps, env, label, token, lock_duration)
+        }
+        ExecuteMsg::Redeem { vault_id, shares } => execute_redeem(deps, env, info, vault_id, shares),
+        ExecuteMsg::Propose { vault_id, title, description, action, latest } => {
+            execute_propose(deps, env, info, vault_id, title, description, action, latest)
+        }
+        ExecuteMsg::Vote { proposal_id, vote } => execute_vote(deps, env, info, proposal_id, vote),
+        ExecuteMsg::Execute { proposal_id } => execute_proposal(deps, env, proposal_id),
+        ExecuteMsg::Close { proposal_id } => execute_close(deps, env, proposal_id),
+        ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, env, info, msg),
+        ExecuteMsg::ProposeNewAdmin { new_admin } => execute_propose_new_admin(deps, info, new_admin),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
+        ExecuteMsg::RenounceAdmin {} => execute_renounce_admin(deps, info),
+        ExecuteMsg::SetPauser { pauser } => execute_set_pauser(deps, info, pauser),
+        ExecuteMsg::Pause { deposits, withdrawals, vault_creation, until } => {
+            execute_pause(deps, env, info, deposits, withdrawals, vault_creation, until)
+        }
+        ExecuteMsg::Unpause {} => execute_unpause(deps, info),
+        ExecuteMsg::StartWindDown {} => execute_start_wind_down(deps, env, info),
+        ExecuteMsg::CancelWindDown {} => execute_cancel_wind_down(deps, env, info),
+        ExecuteMsg::EmergencyExit {} => execute_emergency_exit(deps, env, info),
+        ExecuteMsg::AddToken { token } => execute_add_token(deps, info, token),
+        ExecuteMsg::RemoveToken { token } => execute_remove_token(deps, info, token),
+        ExecuteMsg::AddCollection { collection } => execute_add_collection(deps, info, collection),
+        ExecuteMsg::RemoveCollection { collection } => execute_remove_collection(deps, info, collection),
+    }
+}
 
-            if amount_sum != amount {
-                return get_withdraw_fail_msg(vault.collected);
-            }
+pub fn execute_create_vault(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    label: Option<String>,
+    lock_duration: Option<Duration>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.vault_creation)?;
+    // check if vault with the same label exists
+    if let Some(label) = &label {
+        if find_vault_by_label(deps.storage, &info.sender, label)?.is_some() {
+            return Err(ContractError::VaultAlreadyExists {});
+        }
+    }
+    if let Some(lock_duration) = lock_duration {
+        let config = CONFIG.load(deps.storage)?;
+        if !is_lock_in_bounds(&config, &env.block, &lock_duration.after(&env.block)) {
+            return Err(ContractError::InvalidLockPeriod {});
+        }
+    }
 
-            vault.collected -= amount;
-            remove_empty_ledger(ledgers);
+    let vault_id = NEXT_VAULT_ID.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    NEXT_VAULT_ID.save(deps.storage, &info.sender, &(vault_id + 1))?;
+    let vault = Vault {
+        owner_addr: info.sender.clone(),
+        vault_id,
+        label,
+        lock_duration,
+    };
+    VAULTS.save(deps.storage, (&info.sender, vault_id), &vault)?;
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_create_vault")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+    )
+}
 
-            // save updated vault
-            VAULTS.save(deps.storage, info.sender.clone(), &vault)?;
+pub fn find_vault_by_label(storage: &dyn Storage, owner: &Addr, label: &str) -> StdResult<Option<Vault>> {
+    for item in VAULTS.prefix(owner).range(storage, None, None, Order::Ascending) {
+        let (_, vault) = item?;
+        if vault.label.as_deref() == Some(label) {
+            return Ok(Some(vault));
+        }
+    }
+    Ok(None)
+}
 
-            // send CW20 to user
-            let config = CONFIG.load(deps.storage)?;
-            let cw20 = Cw20Contract(config.cw20_addr);
+pub fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    // funds should go through Unbond and Claim if unbonding period is set
+    if config.unbonding_period.is_some() {
+        return Err(ContractError::UnbondingRequired {});
+    }
 
-            // Build a cw20 transfer send msg
-            let msg = cw20.call(Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.clone().to_string(),
-                amount: amount,
-            })?;
+    let balance = withdraw_vault(deps.storage, &env.block, info.sender.clone(), vault_id, token, amount)?;
 
-            Ok(Response::new()
-                .add_attribute("method", "execute_withdraw")
-                .add_attribute("is_success", "true")
-                .add_attribute("get_amount", amount)
-                .add_attribute("remaining_amount", vault.collected)
-                .add_message(msg)
-            )
-        }
-        ExecuteMsg::Receive(msg) => {
-            let config = CONFIG.load(deps.storage)?;
-            // ExecuteMsg::Receive msg should be sent by cw20 contract
-            if config.cw20_addr != info.sender {
-                return Err(ContractError::Unauthorized {});
-            }
+    // send coins to user
+    let msg = balance.token.transfer_msg(&info.sender, amount)?;
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_withdraw")
+        .add_attribute("token", balance.token.to_string())
+        .add_attribute("get_amount", amount)
+        .add_attribute("remaining_amount", balance.collected)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_message(msg)
+    )
+}
+
+pub fn execute_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let balance = withdraw_vault(deps.storage, &env.block, info.sender.clone(), vault_id, token, amount)?;
+
+    // unbonded coins can be claimed after unbonding period
+    let config = CONFIG.load(deps.storage)?;
+    let release_at = match config.unbonding_period {
+        Some(period) => period.after(&env.block),
+        None => Expiration::AtTime(env.block.time),
+    };
+    add_claim(deps.storage, &info.sender, &Claim { token: balance.token.clone(), amount, release_at })?;
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_unbond")
+        .add_attribute("token", balance.token.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("release_at", release_at.to_string())
+        .add_attribute("remaining_amount", balance.collected)
+        .add_attribute("vault_id", vault_id.to_string())
+    )
+}
 
-            let receive_msg: ReceiveMsg = from_binary(&msg.msg)?;
+// take unlocked coins from vault in order of unlock time and save the updated balance
+pub fn withdraw_vault(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    owner: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+) -> Result<VaultBalance, ContractError> {
+    let mut balance = load_balance(storage, owner, vault_id, token, block)?;
+    let (amount_sum, changed) = if is_winding_down(storage, block)? {
+        // locks don't apply during wind-down. unlocked coins are still taken first
+        let no_penalty = EarlyWithdrawPenalty {
+            rate: Decimal::zero(),
+            linear_decay: false,
+            destination: PenaltyDestination::Redistribute {},
+        };
+        let unlocked = unlocked_entries(storage, &balance, block);
+        let (amount_sum, _, changed) =
+            early_withdraw_from_ledger(unlocked, locked_entries(storage, &balance, block), block, amount, &no_penalty)?;
+        (amount_sum, changed)
+    } else {
+        withdraw_from_ledger(unlocked_entries(storage, &balance, block), block, amount)?
+    };
+    if amount_sum != amount {
+        return Err(withdraw_error(storage, &balance, block, amount_sum, amount));
+    }
 
-            deposit_vault(deps, receive_msg.vault_owner_addr.clone(), msg.amount, env.block.time.nanos())?;
+    balance.collected -= amount;
+    balance.claimed += amount;
+    update_ledger(storage, &mut balance, changed)?;
+    save_balance(storage, &balance)?;
+    Ok(balance)
+}
 
-            Ok(Response::new()
-                .add_attribute("method", "execute_receive")
-                .add_attribute("amount", msg.amount)
-                .add_attribute("timestamp", Uint64::new(env.block.time.nanos()))
-            )
+pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    // pause doesn't hold claims during wind-down
+    if !is_winding_down(deps.storage, &env.block)? {
+        assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    }
+    // oldest MAX_LIMIT claims are looked at per execution, so claim again for the rest
+    let matured = CLAIMS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(MAX_LIMIT as usize)
+        .filter(|item| item.as_ref().map_or(true, |(_, c)| c.release_at.is_expired(&env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+    // one transfer per token
+    let mut amounts: Vec<(AssetInfo, Uint128)> = vec![];
+    for (id, claim) in matured {
+        CLAIMS.remove(deps.storage, (&info.sender, id));
+        match amounts.iter_mut().find(|(token, _)| *token == claim.token) {
+            Some((_, amount)) => *amount += claim.amount,
+            None => amounts.push((claim.token, claim.amount)),
         }
     }
+    if amounts.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+
+    let mut res = Response::new().add_attribute("method", "execute_claim");
+    for (token, amount) in amounts {
+        let msg = token.transfer_msg(&info.sender, amount)?;
+        res = res
+            .add_attribute("token", token.to_string())
+            .add_attribute("amount", amount)
+            .add_message(msg);
+    }
+    Ok(res)
 }
 
-pub fn deposit_vault(deps: DepsMut, addr: Addr, amount: Uint128, timestamp: u64) -> StdResult<()> {
-    let mut vault = VAULTS.load(deps.storage, addr.clone())?;
-    vault.collected += amount;
-    vault.ledger_list.push(Ledger { coin_amount: amount, receive_time: Timestamp::from_nanos(timestamp) });
-    VAULTS.save(deps.storage, addr.clone(), &vault)
+// unbonded coins wait here until release_at
+pub fn add_claim(storage: &mut dyn Storage, owner: &Addr, claim: &Claim) -> StdResult<()> {
+    let id = CLAIM_COUNT.may_load(storage)?.unwrap_or_default();
+    CLAIM_COUNT.save(storage, &(id + 1))?;
+    CLAIMS.save(storage, (owner, id), claim)
 }
 
-pub fn get_withdraw_fail_msg(collected: Uint128) -> Result<Response, ContractError> {
-    return Ok(Response::new()
-        .add_attribute("method", "execute_withdraw")
-        .add_attribute("is_success", "false")
-        .add_attribute("get_amount", Uint128::new(0))
-        .add_attribute("remaining_amount", collected)
+// share received reward tokens among vaults holding staked token pro-rata to their collected coins.
+// vaults owned by this contract are left out, so pool shareholders and multisig members don't earn on their coins
+pub fn execute_fund_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: Cw20ReceiveMsg,
+    staked_token: AssetInfo,
+) -> Result<Response, ContractError> {
+    // reward token should be whitelisted, so claiming rewards doesn't call unknown contracts
+    if !TOKENS.has(deps.storage, &AssetInfo::Cw20(info.sender.clone()).key()) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if msg.amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let staked_key = staked_token.key();
+    let total_collected = TOTAL_COLLECTED.may_load(deps.storage, &staked_key)?.unwrap_or_default();
+    let total = total_collected - EXCLUDED_COLLECTED.may_load(deps.storage, &staked_key)?.unwrap_or_default();
+    if total.is_zero() {
+        return Err(ContractError::NoRewardRecipients { token: staked_token.to_string() });
+    }
+
+    let index = REWARD_INDEX.may_load(deps.storage, (&staked_key, &info.sender))?.unwrap_or_default()
+        + Decimal::from_ratio(msg.amount, total);
+    REWARD_INDEX.save(deps.storage, (&staked_key, &info.sender), &index)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_fund_rewards")
+        .add_attribute("funder", msg.sender)
+        .add_attribute("reward_token", info.sender)
+        .add_attribute("amount", msg.amount)
+        .add_attribute("staked_token", staked_token.to_string())
     )
 }
 
-pub fn remove_empty_ledger(ledgers: &mut Vec<Ledger>) {
-    ledgers
-        .retain(|l| l.coin_amount > Uint128::new(0))
-}
+// settle rewards of vault and send all settled rewards of sender, one transfer per reward token.
+// rewards of other vaults are settled by their deposits and withdrawals, or by claiming them
+pub fn execute_claim_rewards(deps: DepsMut, env: Env, info: MessageInfo, vault_id: u64) -> Result<Response, ContractError> {
+    // pause doesn't hold rewards during wind-down
+    if !is_winding_down(deps.storage, &env.block)? {
+        assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    }
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    let balances = BALANCES
+        .prefix((&info.sender, vault_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, balance)| balance))
+        .collect::<StdResult<Vec<_>>>()?;
+    for balance in balances {
+        settle_rewards(deps.storage, &balance, balance.collected)?;
+    }
+    let rewards = REWARDS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if rewards.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetVault { vault_owner_addr: vault_admin_addr } => to_binary(&query_vault(deps, vault_admin_addr)?),
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    let mut res = Response::new().add_attribute("method", "execute_claim_rewards");
+    for (reward_token, amount) in rewards {
+        REWARDS.remove(deps.storage, (&info.sender, &reward_token));
+        let token = AssetInfo::Cw20(reward_token);
+        res = res
+            .add_attribute("token", token.to_string())
+            .add_attribute("amount", amount)
+            .add_message(token.transfer_msg(&info.sender, amount)?);
     }
+    Ok(res)
 }
 
-fn query_vault(deps: Deps, addr: Addr) -> StdResult<VaultResponse> {
-    let vault = VAULTS.load(deps.storage, addr)?;
-    Ok(VaultResponse {
-        owner_addr: vault.owner_addr.to_string(),
-        collected: vault.collected,
-        ledger_list: vault.ledger_list,
-    })
-}
+pub fn execute_early_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    let config = CONFIG.load(deps.storage)?;
+    let penalty_config = config.early_withdraw_penalty.ok_or(ContractError::EarlyWithdrawDisabled {})?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{from_binary, Addr, CosmosMsg, WasmMsg};
-    use cw20::Cw20ReceiveMsg;
+    let mut balance = load_balance(deps.storage, info.sender.clone(), vault_id, token, &env.block)?;
+    let unlocked = unlocked_entries(deps.storage, &balance, &env.block);
+    let locked = locked_entries(deps.storage, &balance, &env.block);
+    let (amount_sum, penalty, changed) = early_withdraw_from_ledger(unlocked, locked, &env.block, amount, &penalty_config)?;
+    if amount_sum != amount {
+        return Err(ContractError::InsufficientFunds { available: amount_sum, requested: amount });
+    }
 
-    #[test]
-    fn create_vault() {
-        let mut deps = mock_dependencies();
+    balance.collected -= amount;
+    balance.claimed += amount;
+    update_ledger(deps.storage, &mut balance, changed)?;
+    save_balance(deps.storage, &balance)?;
 
-        let msg = InstantiateMsg {
-            cw20_addr: String::from(MOCK_CONTRACT_ADDR),
-        };
-        let info = mock_info("tx_sender", &[]);
+    // penalty goes to treasury, or to remaining vaults pro-rata to collected coins of the token.
+    // if no coins remain in vaults, contract owner receives it, or nobody charges it after admin is renounced
+    let total_collected = TOTAL_COLLECTED.may_load(deps.storage, &balance.token.key())?.unwrap_or_default();
+    let penalty_recipient = match penalty_config.destination {
+        PenaltyDestination::Treasury { address } => Some(address),
+        PenaltyDestination::Redistribute {} if total_collected.is_zero() => config.owner,
+        PenaltyDestination::Redistribute {} => None,
+    };
+    let penalty = if penalty_recipient.is_none() && total_collected.is_zero() { Uint128::zero() } else { penalty };
 
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let receive_amount = amount - penalty;
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    let mut res = Response::new()
+        .add_attribute("method", "execute_early_withdraw")
+        .add_attribute("token", balance.token.to_string())
+        .add_attribute("get_amount", receive_amount)
+        .add_attribute("penalty", penalty)
+        .add_attribute("remaining_amount", balance.collected)
+        .add_attribute("vault_id", vault_id.to_string());
+    // withdrawn coins wait in claims like unbonded coins, so unbonding period can't be skipped
+    match config.unbonding_period {
+        Some(period) if !receive_amount.is_zero() => {
+            let release_at = period.after(&env.block);
+            add_claim(deps.storage, &info.sender, &Claim { token: balance.token.clone(), amount: receive_amount, release_at })?;
+            res = res.add_attribute("release_at", release_at.to_string());
+        }
+        None if !receive_amount.is_zero() => {
+            res = res.add_message(balance.token.transfer_msg(&info.sender, receive_amount)?);
+        }
+        _ => {}
+    }
+    if penalty.is_zero() {
+        return Ok(res);
+    }
 
-        // create 1st vault
-        let msg = ExecuteMsg::CreateVault{};
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.get(1).unwrap().value, "tx_sender");
+    match penalty_recipient {
+        Some(recipient) => {
+            res = res
+                .add_attribute("penalty_recipient", recipient.to_string())
+                .add_message(balance.token.transfer_msg(&recipient, penalty)?);
+        }
+        None => {
+            let key = balance.token.key();
+            let index = PENALTY_INDEX.may_load(deps.storage, &key)?.unwrap_or_default();
+            PENALTY_INDEX.save(deps.storage, &key, &(index + Decimal::from_ratio(penalty, total_collected)))?;
+            res = res.add_attribute("penalty_recipient", "redistribute");
+        }
+    }
+    Ok(res)
+}
 
-        let msg = QueryMsg::GetVault { vault_owner_addr: info.sender.clone() };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // rewards are not a deposit, so they can be funded while deposits are paused
+    if let Ok(ReceiveHookMsg::FundRewards { staked_token }) = from_json(&msg.msg) {
+        return execute_fund_rewards(deps, info, msg, staked_token);
+    }
+    assert_not_paused(deps.storage, &env.block, |pause| pause.deposits)?;
+    if is_winding_down(deps.storage, &env.block)? {
+        return Err(ContractError::WindingDown {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    // ExecuteMsg::Receive msg should be sent by whitelisted cw20 contract
+    let token = AssetInfo::Cw20(info.sender);
+    if !TOKENS.has(deps.storage, &token.key()) {
+        return Err(ContractError::Unauthorized {});
+    }
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(0),
-                ledger_list: vec![],
+    if msg.amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let receive_msg: ReceiveMsg = from_json(&msg.msg).map_err(|_| ContractError::InvalidReceiveMsg {})?;
+    let vault = load_vault(deps.storage, &receive_msg.vault_owner_addr, receive_msg.vault_id)?;
+    // cw20 sender is the depositor, not the token contract
+    let depositor = deps.api.addr_validate(&msg.sender)?;
+    assert_depositor(deps.storage, &vault, &depositor)?;
+    let unlock_at = deposit_unlock_at(&config, &vault, &env.block, &receive_msg)?;
+    assert_valid_memo(deps.storage, &receive_msg)?;
+    // coins of pooled vault are unlocked, and shares of depositor are locked instead
+    let (unlock_at, shares) = match load_pool(deps.storage, &env, &vault)? {
+        Some(_) => {
+            if receive_msg.vesting.is_some() {
+                return Err(ContractError::InvalidReceiveMsg {});
             }
-        );
+            let shares = mint_shares(deps.storage, &env.block, &vault, &depositor, &token, msg.amount, unlock_at)?;
+            (Expiration::AtTime(env.block.time), Some(shares))
+        }
+        None => (unlock_at, None),
+    };
 
-        // create 2nd vault
-        let msg = ExecuteMsg::CreateVault{};
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-        assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_create_vault")
-            .add_attribute("msg", "vault already exists")
-        );
+    deposit_vault(deps.storage, &env.block, &vault, token.clone(), Ledger {
+        coin_amount: msg.amount,
+        claimed_amount: Uint128::zero(),
+        receive_time: env.block.time,
+        unlock_at,
+        vesting: receive_msg.vesting,
+        depositor: Some(depositor.clone()),
+        memo: receive_msg.memo,
+        reference: receive_msg.reference.clone(),
+    })?;
+
+    let mut res = Response::new()
+        .add_attribute("method", "execute_receive")
+        .add_attribute("token", token.to_string())
+        .add_attribute("amount", msg.amount)
+        .add_attribute("timestamp", Uint64::new(env.block.time.nanos()))
+        .add_attribute("vault_id", vault.vault_id.to_string())
+        .add_attribute("depositor", depositor);
+    if let Some(reference) = receive_msg.reference {
+        res = res.add_attribute("reference", reference);
+    }
+    if let Some(shares) = shares {
+        res = res.add_attribute("shares", shares);
     }
+    Ok(res)
+}
 
-    #[test]
-    fn receive() {
-        let mut deps = mock_dependencies();
+// each native coin sent is recorded as a deposit with the same lock
+pub fn execute_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ReceiveMsg,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.deposits)?;
+    if is_winding_down(deps.storage, &env.block)? {
+        return Err(ContractError::WindingDown {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if info.funds.iter().all(|c| c.amount.is_zero()) {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let vault = load_vault(deps.storage, &msg.vault_owner_addr, msg.vault_id)?;
+    assert_depositor(deps.storage, &vault, &info.sender)?;
+    let unlock_at = deposit_unlock_at(&config, &vault, &env.block, &msg)?;
+    let deposits: Vec<(AssetInfo, Uint128)> = info
+        .funds
+        .into_iter()
+        .filter(|c| !c.amount.is_zero())
+        .map(|c| (AssetInfo::Native(c.denom), c.amount))
+        .collect();
+    if let Some((token, _)) = deposits.iter().find(|(token, _)| !TOKENS.has(deps.storage, &token.key())) {
+        return Err(ContractError::TokenNotWhitelisted { token: token.to_string() });
+    }
+    // payment reference identifies a single deposit
+    if msg.reference.is_some() && deposits.len() > 1 {
+        return Err(ContractError::InvalidReceiveMsg {});
+    }
+    assert_valid_memo(deps.storage, &msg)?;
+    let pool = load_pool(deps.storage, &env, &vault)?;
+    if let Some(pool) = &pool {
+        if msg.vesting.is_some() {
+            return Err(ContractError::InvalidReceiveMsg {});
+        }
+        if deposits.iter().any(|(token, _)| *token != pool.token) {
+            return Err(ContractError::InvalidPoolToken { token: pool.token.to_string() });
+        }
+    }
 
-        let msg = InstantiateMsg {
-            cw20_addr: String::from(MOCK_CONTRACT_ADDR),
+    let mut res = Response::new().add_attribute("method", "execute_deposit");
+    for (token, amount) in deposits {
+        let ledger_unlock_at = if pool.is_some() {
+            let shares = mint_shares(deps.storage, &env.block, &vault, &info.sender, &token, amount, unlock_at)?;
+            res = res.add_attribute("shares", shares);
+            Expiration::AtTime(env.block.time)
+        } else {
+            unlock_at
         };
-        let info = mock_info("tx_sender", &[]);
-
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        deposit_vault(deps.storage, &env.block, &vault, token.clone(), Ledger {
+            coin_amount: amount,
+            claimed_amount: Uint128::zero(),
+            receive_time: env.block.time,
+            unlock_at: ledger_unlock_at,
+            vesting: msg.vesting.clone(),
+            depositor: Some(info.sender.clone()),
+            memo: msg.memo.clone(),
+            reference: msg.reference.clone(),
+        })?;
+        res = res
+            .add_attribute("token", token.to_string())
+            .add_attribute("amount", amount);
+    }
+    res = res
+        .add_attribute("timestamp", Uint64::new(env.block.time.nanos()))
+        .add_attribute("vault_id", vault.vault_id.to_string())
+        .add_attribute("depositor", info.sender);
+    if let Some(reference) = msg.reference {
+        res = res.add_attribute("reference", reference);
+    }
+    Ok(res)
+}
 
-        // create vault
-        let msg = ExecuteMsg::CreateVault{};
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.get(1).unwrap().value, "tx_sender");
+// NFT is released all at once, so vesting can't be set. memo and reference are only recorded for coins
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.deposits)?;
+    if is_winding_down(deps.storage, &env.block)? {
+        return Err(ContractError::WindingDown {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    // ExecuteMsg::ReceiveNft msg should be sent by whitelisted collection
+    if !COLLECTIONS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let receive_msg: ReceiveMsg = from_json(&msg.msg).map_err(|_| ContractError::InvalidReceiveMsg {})?;
+    if receive_msg.vesting.is_some() || receive_msg.memo.is_some() || receive_msg.reference.is_some() {
+        return Err(ContractError::InvalidReceiveMsg {});
+    }
+    let vault = load_vault(deps.storage, &receive_msg.vault_owner_addr, receive_msg.vault_id)?;
+    if load_pool(deps.storage, &env, &vault)?.is_some() {
+        return Err(ContractError::InvalidReceiveMsg {});
+    }
+    assert_depositor(deps.storage, &vault, &deps.api.addr_validate(&msg.sender)?)?;
+    let unlock_at = deposit_unlock_at(&config, &vault, &env.block, &receive_msg)?;
 
-        // receive Cw20ReceiveMsg from cw20 contract
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from(MOCK_CONTRACT_ADDR),
-            amount: Uint128::new(100),
-            msg: to_binary(&ReceiveMsg { vault_owner_addr: Addr::unchecked("tx_sender") }).unwrap(),
-        });
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(120);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_receive")
-            .add_attribute("amount", Uint128::new(100))
-            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(120).nanos()))
-        );
+    let key = ((&vault.owner_addr, vault.vault_id), &info.sender, msg.token_id.as_str());
+    NFTS.save(deps.storage, key, &NftLedger {
+        receive_time: env.block.time,
+        unlock_at,
+    })?;
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    Ok(Response::new()
+        .add_attribute("method", "execute_receive_nft")
+        .add_attribute("collection", info.sender)
+        .add_attribute("token_id", msg.token_id)
+        .add_attribute("timestamp", Uint64::new(env.block.time.nanos()))
+        .add_attribute("vault_id", vault.vault_id.to_string())
+    )
+}
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(100),
-                ledger_list: vec![Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(120) }],
-            }
-        );
+pub fn execute_withdraw_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    collection: Addr,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    let key = ((&info.sender, vault_id), &collection, token_id.as_str());
+    let nft = NFTS.may_load(deps.storage, key)?.ok_or(ContractError::NftNotFound {})?;
+    // locks don't apply during wind-down
+    if !nft.unlock_at.is_expired(&env.block) && !is_winding_down(deps.storage, &env.block)? {
+        return Err(ContractError::FundsLocked { unlock_at: nft.unlock_at });
     }
+    NFTS.remove(deps.storage, key);
 
-    #[test]
-    fn withdraw() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            cw20_addr: String::from(MOCK_CONTRACT_ADDR),
-        };
-        let info = mock_info("tx_sender", &[]);
+    let msg = WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: info.sender.to_string(),
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    };
 
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(0);
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_withdraw_nft")
+        .add_attribute("collection", collection)
+        .add_attribute("token_id", token_id)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_message(msg)
+    )
+}
 
-        let _res = instantiate(deps.as_mut(), env, info.clone(), msg).unwrap();
+pub fn execute_propose_vault_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    new_owner: String,
+    expiry: Option<Expiration>,
+    merge: bool,
+) -> Result<Response, ContractError> {
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    if new_owner == info.sender {
+        return Err(ContractError::InvalidNewOwner {});
+    }
+    if expiry.is_some_and(|e| e.is_expired(&env.block)) {
+        return Err(ContractError::TransferExpired {});
+    }
+    // new owner can have only one incoming transfer, unless the other one is expired
+    if let Some((owner, id)) = INCOMING_TRANSFERS.may_load(deps.storage, &new_owner)? {
+        let other = PENDING_TRANSFERS.load(deps.storage, (&owner, id))?;
+        if (&owner, id) != (&info.sender, vault_id) && !other.expiry.is_some_and(|e| e.is_expired(&env.block)) {
+            return Err(ContractError::TransferAlreadyPending {});
+        }
+        PENDING_TRANSFERS.remove(deps.storage, (&owner, id));
+    }
+    if let Some(prev) = PENDING_TRANSFERS.may_load(deps.storage, (&info.sender, vault_id))? {
+        INCOMING_TRANSFERS.remove(deps.storage, &prev.new_owner);
+    }
 
-        // create vault after 1s
-        let msg = ExecuteMsg::CreateVault{};
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(1);
+    let transfer = VaultTransfer {
+        owner: info.sender.clone(),
+        vault_id,
+        new_owner: new_owner.clone(),
+        expiry,
+        merge,
+    };
+    PENDING_TRANSFERS.save(deps.storage, (&info.sender, vault_id), &transfer)?;
+    INCOMING_TRANSFERS.save(deps.storage, &new_owner, &(info.sender.clone(), vault_id))?;
 
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.get(1).unwrap().value, "tx_sender");
+    let mut event = Event::new("vault_transfer_proposed")
+        .add_attribute("owner", info.sender.clone())
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("new_owner", new_owner.clone())
+        .add_attribute("merge", merge.to_string());
+    if let Some(expiry) = expiry {
+        event = event.add_attribute("expiry", expiry.to_string());
+    }
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_propose_vault_transfer")
+        .add_attribute("owner", info.sender)
+        .add_attribute("new_owner", new_owner)
+        .add_event(event)
+    )
+}
 
-        // Deposit 100 coin after 1 min of vault creation
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(60);
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from(MOCK_CONTRACT_ADDR),
-            amount: Uint128::new(100),
-            msg: to_binary(&ReceiveMsg { vault_owner_addr: Addr::unchecked("tx_sender") }).unwrap(),
-        });
+// move vault of proposer to sender as a new vault, or into the vault into_vault_id of sender.
+// new vault keeps its label and lock duration, so label can't be the same as another vault of sender
+pub fn execute_accept_vault_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    into_vault_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let (owner, vault_id) = INCOMING_TRANSFERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoPendingTransfer {})?;
+    let transfer = PENDING_TRANSFERS.load(deps.storage, (&owner, vault_id))?;
+    if transfer.expiry.is_some_and(|e| e.is_expired(&env.block)) {
+        return Err(ContractError::TransferExpired {});
+    }
+    let vault = load_vault(deps.storage, &owner, vault_id)?;
+    assert_movable(deps.storage, (&owner, vault_id), 0)?;
+    let new_vault_id = match into_vault_id {
+        Some(_) if !transfer.merge => return Err(ContractError::MergeNotAllowed {}),
+        Some(into_vault_id) => load_vault(deps.storage, &info.sender, into_vault_id)?.vault_id,
+        None => copy_vault(deps.storage, vault, &info.sender)?,
+    };
+    PENDING_TRANSFERS.remove(deps.storage, (&owner, vault_id));
+    INCOMING_TRANSFERS.remove(deps.storage, &info.sender);
+
+    move_vault(deps.storage, &env.block, (&owner, vault_id), (&info.sender, new_vault_id))?;
+    VAULTS.remove(deps.storage, (&owner, vault_id));
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    let event = Event::new("vault_transfer_accepted")
+        .add_attribute("owner", owner.clone())
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("new_owner", info.sender.clone())
+        .add_attribute("new_vault_id", new_vault_id.to_string())
+        .add_attribute("merged", into_vault_id.is_some().to_string());
+    Ok(Response::new()
+        .add_attribute("method", "execute_accept_vault_transfer")
+        .add_attribute("owner", owner)
+        .add_attribute("new_owner", info.sender)
+        .add_event(event)
+    )
+}
+
+// save vault as a new vault of new owner and return its id. label can't be the same as another vault of new owner
+pub fn copy_vault(storage: &mut dyn Storage, vault: Vault, new_owner: &Addr) -> Result<u64, ContractError> {
+    if let Some(label) = &vault.label {
+        if find_vault_by_label(storage, new_owner, label)?.is_some() {
+            return Err(ContractError::VaultAlreadyExists {});
+        }
+    }
+    let new_vault_id = NEXT_VAULT_ID.may_load(storage, new_owner)?.unwrap_or_default();
+    NEXT_VAULT_ID.save(storage, new_owner, &(new_vault_id + 1))?;
+    let new_vault = Vault {
+        owner_addr: new_owner.clone(),
+        vault_id: new_vault_id,
+        ..vault
+    };
+    VAULTS.save(storage, (new_owner, new_vault_id), &new_vault)?;
+    Ok(new_vault_id)
+}
+
+pub fn execute_cancel_vault_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+) -> Result<Response, ContractError> {
+    let transfer = PENDING_TRANSFERS
+        .may_load(deps.storage, (&info.sender, vault_id))?
+        .ok_or(ContractError::NoPendingTransfer {})?;
+    PENDING_TRANSFERS.remove(deps.storage, (&info.sender, vault_id));
+    INCOMING_TRANSFERS.remove(deps.storage, &transfer.new_owner);
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    let event = Event::new("vault_transfer_cancelled")
+        .add_attribute("owner", info.sender.clone())
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("new_owner", transfer.new_owner);
+    Ok(Response::new()
+        .add_attribute("method", "execute_cancel_vault_transfer")
+        .add_attribute("owner", info.sender)
+        .add_event(event)
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_increase_vault_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    spender: String,
+    token: AssetInfo,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    let token = validate_asset(deps.api, &token)?;
+    let token_key = token.key();
+    let key = ((&info.sender, vault_id), &spender, token_key.as_str());
+    let allowance = ALLOWANCES.update(deps.storage, key, |allow| -> Result<_, ContractError> {
+        let mut val = allow.unwrap_or(Allowance {
+            token: token.clone(),
+            allowance: Uint128::zero(),
+            expires: Expiration::Never {},
+        });
+        if let Some(exp) = expires {
+            if exp.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            val.expires = exp;
+        }
+        val.allowance += amount;
+        Ok(val)
+    })?;
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_increase_vault_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("spender", spender)
+        .add_attribute("token", token.to_string())
+        .add_attribute("allowance", allowance.allowance)
+    )
+}
+
+// allowance is removed when it reaches zero
+#[allow(clippy::too_many_arguments)]
+pub fn execute_decrease_vault_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    spender: String,
+    token: AssetInfo,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    let token_key = token.key();
+    let key = ((&info.sender, vault_id), &spender, token_key.as_str());
+    let mut allowance = ALLOWANCES.may_load(deps.storage, key)?.ok_or(ContractError::NoAllowance {})?;
+    allowance.allowance = allowance.allowance.saturating_sub(amount);
+    if allowance.allowance.is_zero() {
+        ALLOWANCES.remove(deps.storage, key);
+    } else {
+        if let Some(exp) = expires {
+            if exp.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            allowance.expires = exp;
+        }
+        ALLOWANCES.save(deps.storage, key, &allowance)?;
+    }
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_decrease_vault_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("spender", spender)
+        .add_attribute("token", token.to_string())
+        .add_attribute("allowance", allowance.allowance)
+    )
+}
+
+pub fn execute_set_deposit_policy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    policy: DepositPolicy,
+) -> Result<Response, ContractError> {
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    DEPOSIT_POLICIES.save(deps.storage, (&info.sender, vault_id), &policy)?;
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_set_deposit_policy")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("policy", format!("{:?}", policy))
+    )
+}
+
+pub fn execute_update_depositors(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    let add = add.iter().map(|addr| deps.api.addr_validate(addr)).collect::<StdResult<Vec<_>>>()?;
+    let remove = remove.iter().map(|addr| deps.api.addr_validate(addr)).collect::<StdResult<Vec<_>>>()?;
+    for depositor in &remove {
+        DEPOSITORS.remove(deps.storage, ((&info.sender, vault_id), depositor));
+    }
+    for depositor in &add {
+        DEPOSITORS.save(deps.storage, ((&info.sender, vault_id), depositor), &Empty {})?;
+    }
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_update_depositors")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string())
+    )
+}
+
+// vault owner can always deposit into its vault
+pub fn assert_depositor(storage: &dyn Storage, vault: &Vault, depositor: &Addr) -> Result<(), ContractError> {
+    if *depositor == vault.owner_addr {
+        return Ok(());
+    }
+    let key = (&vault.owner_addr, vault.vault_id);
+    let listed = DEPOSITORS.has(storage, (key, depositor));
+    let allowed = match DEPOSIT_POLICIES.may_load(storage, key)?.unwrap_or_default() {
+        DepositPolicy::Open => true,
+        DepositPolicy::Allowlist => listed,
+        DepositPolicy::Denylist => !listed,
+    };
+    if !allowed {
+        return Err(ContractError::DepositNotAllowed {});
+    }
+    Ok(())
+}
+
+// spender withdraws unlocked coins like the vault owner would, and allowance is reduced by amount
+#[allow(clippy::too_many_arguments)]
+pub fn execute_withdraw_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if config.unbonding_period.is_some() {
+        return Err(ContractError::UnbondingRequired {});
+    }
+    let owner = deps.api.addr_validate(&owner)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let token_key = token.key();
+    let key = ((&owner, vault_id), &info.sender, token_key.as_str());
+    let mut allowance = ALLOWANCES.may_load(deps.storage, key)?.ok_or(ContractError::NoAllowance {})?;
+    if allowance.expires.is_expired(&env.block) {
+        return Err(ContractError::AllowanceExpired {});
+    }
+    if allowance.allowance < amount {
+        return Err(ContractError::InsufficientAllowance { allowance: allowance.allowance, requested: amount });
+    }
+
+    let balance = withdraw_vault(deps.storage, &env.block, owner.clone(), vault_id, token, amount)?;
+    allowance.allowance -= amount;
+    if allowance.allowance.is_zero() {
+        ALLOWANCES.remove(deps.storage, key);
+    } else {
+        ALLOWANCES.save(deps.storage, key, &allowance)?;
+    }
+
+    let msg = balance.token.transfer_msg(&recipient, amount)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_withdraw_from")
+        .add_attribute("owner", owner)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("spender", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token", balance.token.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("remaining_allowance", allowance.allowance)
+        .add_message(msg)
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_set_guardians(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    guardians: Vec<String>,
+    threshold: u64,
+    delay: Duration,
+) -> Result<Response, ContractError> {
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    let mut validated: Vec<Addr> = vec![];
+    for guardian in guardians {
+        let guardian = deps.api.addr_validate(&guardian)?;
+        if guardian == info.sender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        if !validated.contains(&guardian) {
+            validated.push(guardian);
+        }
+    }
+    if !validated.is_empty() && (threshold == 0 || threshold > validated.len() as u64) {
+        return Err(ContractError::InvalidGuardianThreshold {});
+    }
+
+    RECOVERIES.remove(deps.storage, (&info.sender, vault_id));
+    if validated.is_empty() {
+        GUARDIANS.remove(deps.storage, (&info.sender, vault_id));
+    } else {
+        GUARDIANS.save(deps.storage, (&info.sender, vault_id), &Guardians { guardians: validated, threshold, delay })?;
+    }
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_set_guardians")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("threshold", threshold.to_string())
+    )
+}
+
+pub fn execute_initiate_recovery(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    vault_id: u64,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    if new_owner == owner {
+        return Err(ContractError::InvalidNewOwner {});
+    }
+    let guardians = load_guardians(deps.storage, &owner, vault_id, &info.sender)?;
+    if RECOVERIES.has(deps.storage, (&owner, vault_id)) {
+        return Err(ContractError::RecoveryAlreadyPending {});
+    }
+
+    let mut recovery = Recovery { new_owner: new_owner.clone(), approvals: vec![], ready_at: None };
+    approve_recovery(&mut recovery, &guardians, &env.block, info.sender.clone());
+    RECOVERIES.save(deps.storage, (&owner, vault_id), &recovery)?;
+
+    let mut event = Event::new("vault_recovery_initiated")
+        .add_attribute("owner", owner.clone())
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("new_owner", new_owner)
+        .add_attribute("guardian", info.sender);
+    if let Some(ready_at) = recovery.ready_at {
+        event = event.add_attribute("ready_at", ready_at.to_string());
+    }
+    Ok(Response::new()
+        .add_attribute("method", "execute_initiate_recovery")
+        .add_attribute("owner", owner)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_event(event)
+    )
+}
+
+pub fn execute_approve_recovery(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    vault_id: u64,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let guardians = load_guardians(deps.storage, &owner, vault_id, &info.sender)?;
+    let mut recovery = RECOVERIES
+        .may_load(deps.storage, (&owner, vault_id))?
+        .ok_or(ContractError::NoPendingRecovery {})?;
+    if recovery.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
+
+    approve_recovery(&mut recovery, &guardians, &env.block, info.sender.clone());
+    RECOVERIES.save(deps.storage, (&owner, vault_id), &recovery)?;
+
+    let mut res = Response::new()
+        .add_attribute("method", "execute_approve_recovery")
+        .add_attribute("owner", owner)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("guardian", info.sender)
+        .add_attribute("approvals", recovery.approvals.len().to_string());
+    if let Some(ready_at) = recovery.ready_at {
+        res = res.add_attribute("ready_at", ready_at.to_string());
+    }
+    Ok(res)
+}
+
+pub fn execute_cancel_recovery(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+) -> Result<Response, ContractError> {
+    let recovery = RECOVERIES
+        .may_load(deps.storage, (&info.sender, vault_id))?
+        .ok_or(ContractError::NoPendingRecovery {})?;
+    // only during the delay. once it is over anyone can execute recovery
+    if recovery.ready_at.is_some_and(|ready_at| ready_at.is_expired(&env.block)) {
+        return Err(ContractError::RecoveryReady {});
+    }
+    RECOVERIES.remove(deps.storage, (&info.sender, vault_id));
+
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    let event = Event::new("vault_recovery_cancelled")
+        .add_attribute("owner", info.sender.clone())
+        .add_attribute("vault_id", vault_id.to_string());
+    Ok(Response::new()
+        .add_attribute("method", "execute_cancel_recovery")
+        .add_attribute("owner", info.sender)
+        .add_event(event)
+    )
+}
+
+// move vault to new owner like AcceptVaultTransfer. guardians keep protecting the vault of new owner.
+// settled rewards and pending claims belong to owner, not vault, and old owner lost the key to them, so they move too
+pub fn execute_recovery(deps: DepsMut, env: Env, owner: String, vault_id: u64) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let recovery = RECOVERIES
+        .may_load(deps.storage, (&owner, vault_id))?
+        .ok_or(ContractError::NoPendingRecovery {})?;
+    if !recovery.ready_at.is_some_and(|ready_at| ready_at.is_expired(&env.block)) {
+        return Err(ContractError::RecoveryNotReady {});
+    }
+    let mut vault = load_vault(deps.storage, &owner, vault_id)?;
+    let claims = CLAIMS.prefix(&owner).keys_raw(deps.storage, None, None, Order::Ascending).take(MAX_MOVE_ENTRIES + 1).count();
+    assert_movable(deps.storage, (&owner, vault_id), claims)?;
+    let new_owner = recovery.new_owner;
+    // new owner may have taken the label meanwhile. drop it rather than block recovery
+    if let Some(label) = &vault.label {
+        if find_vault_by_label(deps.storage, &new_owner, label)?.is_some() {
+            vault.label = None;
+        }
+    }
+    let new_vault_id = copy_vault(deps.storage, vault, &new_owner)?;
+
+    let guardians = GUARDIANS.load(deps.storage, (&owner, vault_id))?;
+    GUARDIANS.save(deps.storage, (&new_owner, new_vault_id), &guardians)?;
+    if let Some(transfer) = PENDING_TRANSFERS.may_load(deps.storage, (&owner, vault_id))? {
+        PENDING_TRANSFERS.remove(deps.storage, (&owner, vault_id));
+        INCOMING_TRANSFERS.remove(deps.storage, &transfer.new_owner);
+    }
+    move_vault(deps.storage, &env.block, (&owner, vault_id), (&new_owner, new_vault_id))?;
+    VAULTS.remove(deps.storage, (&owner, vault_id));
+    // rewards accrued in the vault were settled to old owner by move_vault
+    move_rewards_and_claims(deps.storage, &owner, &new_owner)?;
+
+    touch_owner(deps.storage, &new_owner, &env.block)?;
+    let event = Event::new("vault_recovered")
+        .add_attribute("owner", owner.clone())
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("new_owner", new_owner.clone())
+        .add_attribute("new_vault_id", new_vault_id.to_string());
+    Ok(Response::new()
+        .add_attribute("method", "execute_recovery")
+        .add_attribute("owner", owner)
+        .add_attribute("new_owner", new_owner)
+        .add_event(event)
+    )
+}
+
+// settled rewards and claims of owner go to new owner. claims get new ids, in the same order
+pub fn move_rewards_and_claims(storage: &mut dyn Storage, owner: &Addr, new_owner: &Addr) -> StdResult<()> {
+    let rewards = REWARDS
+        .prefix(owner)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (reward_token, amount) in rewards {
+        REWARDS.remove(storage, (owner, &reward_token));
+        REWARDS.update(storage, (new_owner, &reward_token), |rewards| -> StdResult<_> {
+            Ok(rewards.unwrap_or_default() + amount)
+        })?;
+    }
+    let claims = CLAIMS
+        .prefix(owner)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, claim) in claims {
+        CLAIMS.remove(storage, (owner, id));
+        add_claim(storage, new_owner, &claim)?;
+    }
+    Ok(())
+}
+
+// guardians of vault, if sender is one of them
+pub fn load_guardians(storage: &dyn Storage, owner: &Addr, vault_id: u64, sender: &Addr) -> Result<Guardians, ContractError> {
+    let guardians = GUARDIANS.may_load(storage, (owner, vault_id))?.ok_or(ContractError::Unauthorized {})?;
+    if !guardians.guardians.contains(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(guardians)
+}
+
+// delay starts when approvals reach threshold
+pub fn approve_recovery(recovery: &mut Recovery, guardians: &Guardians, block: &BlockInfo, guardian: Addr) {
+    recovery.approvals.push(guardian);
+    if recovery.ready_at.is_none() && recovery.approvals.len() as u64 >= guardians.threshold {
+        recovery.ready_at = Some(guardians.delay.after(block));
+    }
+}
+
+pub fn execute_set_beneficiaries(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    beneficiaries: Vec<Beneficiary>,
+    inactivity_period: Duration,
+) -> Result<Response, ContractError> {
+    load_vault(deps.storage, &info.sender, vault_id)?;
+    let mut total_share = Decimal::zero();
+    for (i, beneficiary) in beneficiaries.iter().enumerate() {
+        deps.api.addr_validate(beneficiary.addr.as_str())?;
+        if beneficiary.share.is_zero() || beneficiaries[..i].iter().any(|b| b.addr == beneficiary.addr) {
+            return Err(ContractError::InvalidBeneficiaries {});
+        }
+        total_share += beneficiary.share;
+    }
+    if !beneficiaries.is_empty() && total_share != Decimal::one() {
+        return Err(ContractError::InvalidBeneficiaries {});
+    }
+
+    // claims under the previous split are forgotten
+    clear_inheritance(deps.storage, (&info.sender, vault_id))?;
+    if !beneficiaries.is_empty() {
+        INHERITANCES.save(deps.storage, (&info.sender, vault_id), &Inheritance { beneficiaries, inactivity_period })?;
+    }
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_set_beneficiaries")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_id", vault_id.to_string())
+    )
+}
+
+pub fn execute_heartbeat(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    touch_owner(deps.storage, &info.sender, &env.block)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_heartbeat")
+        .add_attribute("owner", info.sender)
+    )
+}
+
+// beneficiary is entitled to its share of coins in vault plus coins all beneficiaries already claimed.
+// only unlocked coins are withdrawn, so the rest can be claimed again as deposits unlock
+pub fn execute_claim_inheritance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    vault_id: u64,
+    token: AssetInfo,
+) -> Result<Response, ContractError> {
+    // pause doesn't hold inheritance during wind-down
+    if !is_winding_down(deps.storage, &env.block)? {
+        assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    }
+    let owner = deps.api.addr_validate(&owner)?;
+    let inheritance = INHERITANCES
+        .may_load(deps.storage, (&owner, vault_id))?
+        .ok_or(ContractError::NoInheritance {})?;
+    let beneficiary = inheritance
+        .beneficiaries
+        .iter()
+        .find(|b| b.addr == info.sender)
+        .ok_or(ContractError::Unauthorized {})?;
+    let claimable_at = LAST_ACTIVITY.load(deps.storage, &owner)?.expires(&inheritance.inactivity_period);
+    if !claimable_at.is_expired(&env.block) {
+        return Err(ContractError::OwnerActive { claimable_at });
+    }
+
+    let token_key = token.key();
+    let (balance, share) = read_balance_share(deps.storage, owner.clone(), vault_id, token.clone(), &env.block)?;
+    let mut total_claimed = Uint128::zero();
+    for b in &inheritance.beneficiaries {
+        total_claimed += INHERITED.may_load(deps.storage, ((&owner, vault_id), &b.addr, &token_key))?.unwrap_or_default();
+    }
+    let claimed = INHERITED.may_load(deps.storage, ((&owner, vault_id), &info.sender, &token_key))?.unwrap_or_default();
+    let entitled = ((balance.collected + total_claimed) * beneficiary.share).saturating_sub(claimed);
+    if entitled.is_zero() {
+        return Err(ContractError::NothingToInherit {});
+    }
+    let unlocked = share.into_iter().map(Ok).chain(unlocked_entries(deps.storage, &balance, &env.block));
+    let (available, _) = withdraw_from_ledger(unlocked, &env.block, entitled)?;
+    if available.is_zero() {
+        return Err(withdraw_error(deps.storage, &balance, &env.block, available, entitled));
+    }
+    let amount = entitled.min(available);
+
+    let balance = withdraw_vault(deps.storage, &env.block, owner.clone(), vault_id, token, amount)?;
+    INHERITED.save(deps.storage, ((&owner, vault_id), &info.sender, &token_key), &(claimed + amount))?;
+
+    let res = Response::new()
+        .add_attribute("method", "execute_claim_inheritance")
+        .add_attribute("owner", owner)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("beneficiary", info.sender.clone())
+        .add_attribute("token", balance.token.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("remaining_amount", balance.collected);
+    // inherited coins wait in claims of beneficiary like Unbond
+    let config = CONFIG.load(deps.storage)?;
+    match config.unbonding_period {
+        Some(period) => {
+            let release_at = period.after(&env.block);
+            add_claim(deps.storage, &info.sender, &Claim { token: balance.token, amount, release_at })?;
+            Ok(res.add_attribute("release_at", release_at.to_string()))
+        }
+        None => Ok(res.add_message(balance.token.transfer_msg(&info.sender, amount)?)),
+    }
+}
+
+// record owner action, which resets inactivity period of its vaults
+pub fn touch_owner(storage: &mut dyn Storage, owner: &Addr, block: &BlockInfo) -> StdResult<()> {
+    LAST_ACTIVITY.save(storage, owner, &Activity { height: block.height, time: block.time })
+}
+
+pub fn clear_inheritance(storage: &mut dyn Storage, key: VaultKey) -> StdResult<()> {
+    INHERITANCES.remove(storage, key);
+    let inherited = INHERITED
+        .sub_prefix(key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (beneficiary, token_key) in inherited {
+        INHERITED.remove(storage, (key, &beneficiary, &token_key));
+    }
+    Ok(())
+}
+
+// vault is owned by this contract, so coins can only leave it through passed proposals.
+// labels are not checked since every multisig vault has the same owner
+pub fn execute_create_multisig_vault(
+    deps: DepsMut,
+    env: Env,
+    label: Option<String>,
+    lock_duration: Option<Duration>,
+    members: Vec<Member>,
+    threshold: u64,
+    max_voting_period: Duration,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.vault_creation)?;
+    if let Some(lock_duration) = lock_duration {
+        let config = CONFIG.load(deps.storage)?;
+        if !is_lock_in_bounds(&config, &env.block, &lock_duration.after(&env.block)) {
+            return Err(ContractError::InvalidLockPeriod {});
+        }
+    }
+    // later entry wins if the same member is listed twice
+    let mut weights: Vec<(Addr, u64)> = vec![];
+    for member in members {
+        deps.api.addr_validate(member.addr.as_str())?;
+        weights.retain(|(addr, _)| addr != member.addr);
+        if member.weight > 0 {
+            weights.push((member.addr, member.weight));
+        }
+    }
+    let total_weight = weights.iter().map(|(_, weight)| weight).sum();
+    if threshold == 0 || threshold > total_weight {
+        return Err(ContractError::InvalidThreshold {});
+    }
+
+    let owner = env.contract.address.clone();
+    let vault_id = NEXT_VAULT_ID.may_load(deps.storage, &owner)?.unwrap_or_default();
+    NEXT_VAULT_ID.save(deps.storage, &owner, &(vault_id + 1))?;
+    let vault = Vault {
+        owner_addr: owner.clone(),
+        vault_id,
+        label,
+        lock_duration,
+    };
+    VAULTS.save(deps.storage, (&owner, vault_id), &vault)?;
+    for (addr, weight) in &weights {
+        MEMBERS.save(deps.storage, (vault_id, addr), weight, env.block.height)?;
+    }
+    MULTISIGS.save(deps.storage, vault_id, &Multisig { threshold, total_weight, max_voting_period })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_create_multisig_vault")
+        .add_attribute("owner", owner)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("threshold", threshold.to_string())
+        .add_attribute("total_weight", total_weight.to_string())
+    )
+}
+
+pub fn execute_create_pooled_vault(
+    deps: DepsMut,
+    env: Env,
+    label: Option<String>,
+    token: AssetInfo,
+    lock_duration: Option<Duration>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, &env.block, |pause| pause.vault_creation)?;
+    if let Some(lock_duration) = lock_duration {
+        let config = CONFIG.load(deps.storage)?;
+        if !is_lock_in_bounds(&config, &env.block, &lock_duration.after(&env.block)) {
+            return Err(ContractError::InvalidLockPeriod {});
+        }
+    }
+    if !TOKENS.has(deps.storage, &token.key()) {
+        return Err(ContractError::TokenNotWhitelisted { token: token.to_string() });
+    }
+
+    let owner = env.contract.address.clone();
+    let vault_id = NEXT_VAULT_ID.may_load(deps.storage, &owner)?.unwrap_or_default();
+    NEXT_VAULT_ID.save(deps.storage, &owner, &(vault_id + 1))?;
+    VAULTS.save(deps.storage, (&owner, vault_id), &Vault {
+        owner_addr: owner.clone(),
+        vault_id,
+        label,
+        lock_duration,
+    })?;
+    POOLS.save(deps.storage, vault_id, &Pool { token: token.clone(), total_shares: Uint128::zero() })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_create_pooled_vault")
+        .add_attribute("owner", owner)
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("token", token.to_string())
+    )
+}
+
+// burn shares of sender and withdraw coins they are worth at the current share price
+pub fn execute_redeem(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    shares: Uint128,
+) -> Result<Response, ContractError> {
+    // pause and locks don't hold shares during wind-down
+    let winding_down = is_winding_down(deps.storage, &env.block)?;
+    if !winding_down {
+        assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+    }
+    if shares.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let mut pool = POOLS.may_load(deps.storage, vault_id)?.ok_or(ContractError::VaultNotFound {})?;
+    let mut holder = POOL_SHARES
+        .may_load(deps.storage, (vault_id, &info.sender))?
+        .ok_or(ContractError::InsufficientFunds { available: Uint128::zero(), requested: shares })?;
+    if holder.shares < shares {
+        return Err(ContractError::InsufficientFunds { available: holder.shares, requested: shares });
+    }
+    if !holder.unlock_at.is_expired(&env.block) && !winding_down {
+        return Err(ContractError::FundsLocked { unlock_at: holder.unlock_at });
+    }
+    let owner = env.contract.address.clone();
+    let total_assets = pool_total_assets(deps.storage, &env.block, &owner, vault_id, &pool)?;
+    let assets = pool.to_assets(shares, total_assets);
+    if assets.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let balance = withdraw_vault(deps.storage, &env.block, owner, vault_id, pool.token.clone(), assets)?;
+    pool.total_shares -= shares;
+    POOLS.save(deps.storage, vault_id, &pool)?;
+    holder.shares -= shares;
+    if holder.shares.is_zero() {
+        POOL_SHARES.remove(deps.storage, (vault_id, &info.sender));
+    } else {
+        POOL_SHARES.save(deps.storage, (vault_id, &info.sender), &holder)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("method", "execute_redeem")
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("token", balance.token.to_string())
+        .add_attribute("shares", shares)
+        .add_attribute("amount", assets);
+    // redeemed coins wait in claims like unbonded coins
+    let config = CONFIG.load(deps.storage)?;
+    match config.unbonding_period {
+        Some(period) => {
+            let release_at = period.after(&env.block);
+            add_claim(deps.storage, &info.sender, &Claim { token: balance.token, amount: assets, release_at })?;
+            Ok(res.add_attribute("release_at", release_at.to_string()))
+        }
+        None => Ok(res.add_message(balance.token.transfer_msg(&info.sender, assets)?)),
+    }
+}
+
+// pool of vault if it's a pooled vault
+pub fn load_pool(storage: &dyn Storage, env: &Env, vault: &Vault) -> StdResult<Option<Pool>> {
+    if vault.owner_addr != env.contract.address {
+        return Ok(None);
+    }
+    POOLS.may_load(storage, vault.vault_id)
+}
+
+// coins of pooled vault with redistributed penalty not added to it yet
+pub fn pool_total_assets(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+    vault_id: u64,
+    pool: &Pool,
+) -> StdResult<Uint128> {
+    let balance = read_balance(storage, owner.clone(), vault_id, pool.token.clone())?
+        .ok_or_else(|| StdError::not_found("Vault"))?;
+    let accrued = penalty_share(storage, &balance, block)?.map(|l| l.coin_amount).unwrap_or_default();
+    Ok(balance.collected + accrued)
+}
+
+// mint shares of pooled vault for amount deposited by depositor. should be called before the coins are
+// added to the vault. lock of the deposit extends the lock of all shares of depositor
+pub fn mint_shares(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    vault: &Vault,
+    depositor: &Addr,
+    token: &AssetInfo,
+    amount: Uint128,
+    unlock_at: Expiration,
+) -> Result<Uint128, ContractError> {
+    let mut pool = POOLS.load(storage, vault.vault_id)?;
+    if *token != pool.token {
+        return Err(ContractError::InvalidPoolToken { token: pool.token.to_string() });
+    }
+    let total_assets = pool_total_assets(storage, block, &vault.owner_addr, vault.vault_id, &pool)?;
+    let shares = pool.to_shares(amount, total_assets);
+    if shares.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    let holder = POOL_SHARES.may_load(storage, (vault.vault_id, depositor))?;
+    let unlock_at = match &holder {
+        Some(holder) if !holder.unlock_at.is_expired(block) => match holder.unlock_at.partial_cmp(&unlock_at) {
+            Some(Ordering::Greater) => holder.unlock_at,
+            Some(_) => unlock_at,
+            // time and height locks can't be combined
+            None => return Err(ContractError::InvalidLockPeriod {}),
+        },
+        _ => unlock_at,
+    };
+    let prev_shares = holder.map(|holder| holder.shares).unwrap_or_default();
+    POOL_SHARES.save(storage, (vault.vault_id, depositor), &PoolShare { shares: prev_shares + shares, unlock_at })?;
+    pool.total_shares += shares;
+    POOLS.save(storage, vault.vault_id, &pool)?;
+    Ok(shares)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_propose(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vault_id: u64,
+    title: String,
+    description: String,
+    action: MultisigAction,
+    latest: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let multisig = MULTISIGS.may_load(deps.storage, vault_id)?.ok_or(ContractError::VaultNotFound {})?;
+    let weight = MEMBERS
+        .may_load(deps.storage, (vault_id, &info.sender))?
+        .filter(|weight| *weight > 0)
+        .ok_or(ContractError::Unauthorized {})?;
+    match &action {
+        MultisigAction::Withdraw { token, amount, recipient } => {
+            if amount.is_zero() {
+                return Err(ContractError::ZeroAmount {});
+            }
+            validate_asset(deps.api, token)?;
+            deps.api.addr_validate(recipient.as_str())?;
+        }
+        MultisigAction::UpdateMembers { add, remove } => {
+            for addr in add.iter().map(|member| &member.addr).chain(remove.iter()) {
+                deps.api.addr_validate(addr.as_str())?;
+            }
+        }
+        // threshold is checked against members when proposal is executed
+        MultisigAction::UpdateThreshold { .. } => {}
+    }
+
+    // proposal can't be open longer than max voting period
+    let max_expires = multisig.max_voting_period.after(&env.block);
+    let expires = latest.unwrap_or(max_expires);
+    let in_period = matches!(expires.partial_cmp(&max_expires), Some(Ordering::Less | Ordering::Equal));
+    if !in_period || expires.is_expired(&env.block) {
+        return Err(ContractError::WrongExpiration {});
+    }
+
+    let mut proposal = Proposal {
+        vault_id,
+        title,
+        description,
+        action,
+        proposer: info.sender.clone(),
+        start_height: env.block.height,
+        expires,
+        threshold: multisig.threshold,
+        total_weight: multisig.total_weight,
+        status: Status::Open,
+        votes: Votes { yes: weight, ..Votes::default() },
+    };
+    proposal.update_status(&env.block);
+    let proposal_id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    PROPOSAL_COUNT.save(deps.storage, &proposal_id)?;
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    BALLOTS.save(deps.storage, (proposal_id, &info.sender), &Ballot { weight, vote: Vote::Yes })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_propose")
+        .add_attribute("vault_id", vault_id.to_string())
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("proposer", info.sender)
+        .add_attribute("status", format!("{:?}", proposal.status))
+    )
+}
+
+pub fn execute_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    vote: Vote,
+) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+    if proposal.status != Status::Open {
+        return Err(ContractError::NotOpen {});
+    }
+    if proposal.expires.is_expired(&env.block) {
+        return Err(ContractError::ProposalExpired {});
+    }
+    // only members at the time proposal was created can vote
+    let weight = MEMBERS
+        .may_load_at_height(deps.storage, (proposal.vault_id, &info.sender), proposal.start_height)?
+        .filter(|weight| *weight > 0)
+        .ok_or(ContractError::Unauthorized {})?;
+    if BALLOTS.has(deps.storage, (proposal_id, &info.sender)) {
+        return Err(ContractError::AlreadyVoted {});
+    }
+
+    BALLOTS.save(deps.storage, (proposal_id, &info.sender), &Ballot { weight, vote: vote.clone() })?;
+    proposal.votes.add_vote(vote, weight);
+    proposal.update_status(&env.block);
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_vote")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("status", format!("{:?}", proposal.status))
+    )
+}
+
+pub fn execute_proposal(deps: DepsMut, env: Env, proposal_id: u64) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+    proposal.update_status(&env.block);
+    if proposal.status != Status::Passed {
+        return Err(ContractError::WrongExecuteStatus {});
+    }
+
+    let vault_id = proposal.vault_id;
+    let mut response = Response::new()
+        .add_attribute("method", "execute_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("vault_id", vault_id.to_string());
+    match &proposal.action {
+        MultisigAction::Withdraw { token, amount, recipient } => {
+            // multisig vault can't emergency exit, so pause and unbonding period don't apply during wind-down
+            let winding_down = is_winding_down(deps.storage, &env.block)?;
+            if !winding_down {
+                assert_not_paused(deps.storage, &env.block, |pause| pause.withdrawals)?;
+            }
+            let config = CONFIG.load(deps.storage)?;
+            let owner = env.contract.address.clone();
+            let balance = withdraw_vault(deps.storage, &env.block, owner, vault_id, token.clone(), *amount)?;
+            response = response
+                .add_attribute("token", balance.token.to_string())
+                .add_attribute("amount", *amount)
+                .add_attribute("recipient", recipient)
+                .add_attribute("remaining_amount", balance.collected);
+            // members can't unbond coins of the contract, so recipient claims them after unbonding period
+            match config.unbonding_period {
+                Some(period) if !winding_down => {
+                    let release_at = period.after(&env.block);
+                    add_claim(deps.storage, recipient, &Claim { token: balance.token, amount: *amount, release_at })?;
+                    response = response.add_attribute("release_at", release_at.to_string());
+                }
+                _ => response = response.add_message(balance.token.transfer_msg(recipient, *amount)?),
+            }
+        }
+        MultisigAction::UpdateMembers { add, remove } => {
+            let mut multisig = MULTISIGS.load(deps.storage, vault_id)?;
+            let mut members = member_weights(deps.storage, vault_id)?;
+            members.retain(|(addr, _)| !remove.contains(addr));
+            for member in add {
+                members.retain(|(addr, _)| addr != member.addr);
+                if member.weight > 0 {
+                    members.push((member.addr.clone(), member.weight));
+                }
+            }
+            let total_weight = members.iter().map(|(_, weight)| weight).sum();
+            if multisig.threshold > total_weight {
+                return Err(ContractError::InvalidThreshold {});
+            }
+
+            for addr in remove {
+                MEMBERS.remove(deps.storage, (vault_id, addr), env.block.height)?;
+            }
+            for member in add {
+                if member.weight > 0 {
+                    MEMBERS.save(deps.storage, (vault_id, &member.addr), &member.weight, env.block.height)?;
+                } else {
+                    MEMBERS.remove(deps.storage, (vault_id, &member.addr), env.block.height)?;
+                }
+            }
+            multisig.total_weight = total_weight;
+            MULTISIGS.save(deps.storage, vault_id, &multisig)?;
+            response = response.add_attribute("total_weight", total_weight.to_string());
+        }
+        MultisigAction::UpdateThreshold { threshold } => {
+            let mut multisig = MULTISIGS.load(deps.storage, vault_id)?;
+            if *threshold == 0 || *threshold > multisig.total_weight {
+                return Err(ContractError::InvalidThreshold {});
+            }
+            multisig.threshold = *threshold;
+            MULTISIGS.save(deps.storage, vault_id, &multisig)?;
+            response = response.add_attribute("threshold", threshold.to_string());
+        }
+    }
+
+    proposal.status = Status::Executed;
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    Ok(response)
+}
+
+pub fn execute_close(deps: DepsMut, env: Env, proposal_id: u64) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+    if [Status::Executed, Status::Rejected, Status::Passed].contains(&proposal.status) {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    // proposal could pass before expiration without being saved as passed
+    if proposal.is_passed() {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    if !proposal.expires.is_expired(&env.block) {
+        return Err(ContractError::NotExpired {});
+    }
+
+    proposal.status = Status::Rejected;
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_close")
+        .add_attribute("proposal_id", proposal_id.to_string())
+    )
+}
+
+pub fn member_weights(storage: &dyn Storage, vault_id: u64) -> StdResult<Vec<(Addr, u64)>> {
+    MEMBERS.prefix(vault_id).range(storage, None, None, Order::Ascending).collect()
+}
+
+// deposits and NFTs of vault, with other entries moved along, should be at most MAX_MOVE_ENTRIES.
+// each token is counted only up to the limit, so the check itself stays bounded
+pub fn assert_movable(storage: &dyn Storage, key: VaultKey, other_entries: usize) -> Result<(), ContractError> {
+    let mut entries = other_entries + NFTS.sub_prefix(key).keys_raw(storage, None, None, Order::Ascending).take(MAX_MOVE_ENTRIES + 1).count();
+    let balances = BALANCES
+        .prefix(key)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, balance)| balance))
+        .collect::<StdResult<Vec<_>>>()?;
+    for balance in balances {
+        entries += ledger_entries(storage, &balance).take(MAX_MOVE_ENTRIES + 1).count();
+    }
+    if entries > MAX_MOVE_ENTRIES {
+        return Err(ContractError::TooManyEntries { max: MAX_MOVE_ENTRIES as u64 });
+    }
+    Ok(())
+}
+
+// move balances, deposits and NFTs of vault to another vault. callers check assert_movable first.
+// deposits get new ledger ids after the deposits the other vault already has.
+// claims and settled rewards belong to owner, not vault, so they are not moved. allowances, beneficiaries, guardians and
+// deposit policy set by owner are removed
+pub fn move_vault(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    from_key: VaultKey,
+    to_key: VaultKey,
+) -> Result<(), ContractError> {
+    let tokens = BALANCES
+        .prefix(from_key)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, balance)| balance.token))
+        .collect::<StdResult<Vec<_>>>()?;
+    for token in tokens {
+        let mut from = load_balance(storage, from_key.0.clone(), from_key.1, token.clone(), block)?;
+        let mut to = load_balance(storage, to_key.0.clone(), to_key.1, token.clone(), block)?;
+
+        let entries = ledger_entries(storage, &from).collect::<StdResult<Vec<_>>>()?;
+        for (id, ledger) in entries {
+            LEDGERS.remove(storage, (from_key, token.key().as_str(), id));
+            remove_unlock(storage, &from, id, &ledger);
+            push_ledger(storage, &mut to, ledger)?;
+        }
+        to.claimed += from.claimed;
+        // keep TOTAL_COLLECTED in sync before removing the balance
+        from.collected = Uint128::zero();
+        save_balance(storage, &from)?;
+        BALANCES.remove(storage, (from_key, token.key().as_str()));
+        save_balance(storage, &to)?;
+    }
+
+    let nfts = NFTS
+        .sub_prefix(from_key)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((collection, token_id), nft) in nfts {
+        NFTS.remove(storage, (from_key, &collection, &token_id));
+        NFTS.save(storage, (to_key, &collection, &token_id), &nft)?;
+    }
+
+    let allowances = ALLOWANCES
+        .sub_prefix(from_key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (spender, token_key) in allowances {
+        ALLOWANCES.remove(storage, (from_key, &spender, &token_key));
+    }
+    clear_inheritance(storage, from_key)?;
+    GUARDIANS.remove(storage, from_key);
+    RECOVERIES.remove(storage, from_key);
+    DEPOSIT_POLICIES.remove(storage, from_key);
+    let depositors = DEPOSITORS
+        .prefix(from_key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for depositor in depositors {
+        DEPOSITORS.remove(storage, (from_key, &depositor));
+    }
+    Ok(())
+}
+
+// unlock time of a deposit from lock options of ReceiveMsg
+pub fn deposit_unlock_at(
+    config: &Config,
+    vault: &Vault,
+    block: &BlockInfo,
+    msg: &ReceiveMsg,
+) -> Result<Expiration, ContractError> {
+    let unlock_at = match (msg.lock_for, msg.unlock_at, &msg.vesting) {
+        (None, None, None) => vault.lock_duration.unwrap_or(config.lock_duration).after(block),
+        (Some(lock_for), None, None) => lock_for.after(block),
+        (None, None, Some(vesting)) => {
+            // vesting deposit is unlocked from cliff time and bounded by its end time
+            if !vesting.is_valid() {
+                return Err(ContractError::InvalidVestingSchedule {});
+            }
+            if !is_lock_in_bounds(config, block, &Expiration::AtTime(vesting.end_time)) {
+                return Err(ContractError::InvalidLockPeriod {});
+            }
+            return Ok(Expiration::AtTime(vesting.cliff_time));
+        }
+        (None, Some(unlock_at), None) => unlock_at,
+        _ => return Err(ContractError::ConflictingLockPeriod {}),
+    };
+    if !is_lock_in_bounds(config, block, &unlock_at) {
+        return Err(ContractError::InvalidLockPeriod {});
+    }
+    Ok(unlock_at)
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: UpdateConfigMsg,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+
+    for field in msg.unset {
+        match field {
+            ConfigField::MinLockDuration => config.min_lock_duration = None,
+            ConfigField::MaxLockDuration => config.max_lock_duration = None,
+            ConfigField::UnbondingPeriod => config.unbonding_period = None,
+            ConfigField::EarlyWithdrawPenalty => config.early_withdraw_penalty = None,
+            ConfigField::WindDownDelay => config.wind_down_delay = None,
+            ConfigField::MaxPauseDuration => config.max_pause_duration = None,
+        }
+    }
+    if let Some(lock_duration) = msg.lock_duration {
+        config.lock_duration = lock_duration;
+    }
+    if msg.min_lock_duration.is_some() {
+        config.min_lock_duration = msg.min_lock_duration;
+    }
+    if msg.max_lock_duration.is_some() {
+        config.max_lock_duration = msg.max_lock_duration;
+    }
+    if msg.unbonding_period.is_some() {
+        config.unbonding_period = msg.unbonding_period;
+    }
+    if msg.early_withdraw_penalty.is_some() {
+        config.early_withdraw_penalty = msg.early_withdraw_penalty;
+    }
+    if msg.wind_down_delay.is_some() {
+        config.wind_down_delay = msg.wind_down_delay;
+    }
+    if msg.max_pause_duration.is_some() {
+        config.max_pause_duration = msg.max_pause_duration;
+    }
+    if !is_lock_in_bounds(&config, &env.block, &config.lock_duration.after(&env.block)) {
+        return Err(ContractError::InvalidLockPeriod {});
+    }
+    validate_penalty(&config.early_withdraw_penalty)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_update_config")
+        .add_attribute("lock_duration", config.lock_duration.to_string())
+    )
+}
+
+pub fn execute_add_token(deps: DepsMut, info: MessageInfo, token: AssetInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    let token = validate_asset(deps.api, &token)?;
+    TOKENS.save(deps.storage, &token.key(), &token)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_add_token")
+        .add_attribute("token", token.to_string())
+    )
+}
+
+// removed token can't be deposited anymore, but coins in vaults can still be withdrawn
+pub fn execute_remove_token(deps: DepsMut, info: MessageInfo, token: AssetInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    TOKENS.remove(deps.storage, &token.key());
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_remove_token")
+        .add_attribute("token", token.to_string())
+    )
+}
+
+pub fn execute_add_collection(deps: DepsMut, info: MessageInfo, collection: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    let collection = deps.api.addr_validate(&collection)?;
+    COLLECTIONS.save(deps.storage, &collection, &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_add_collection")
+        .add_attribute("collection", collection)
+    )
+}
+
+// removed collection can't be deposited anymore, but NFTs in vaults can still be withdrawn
+pub fn execute_remove_collection(deps: DepsMut, info: MessageInfo, collection: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    let collection = deps.api.addr_validate(&collection)?;
+    COLLECTIONS.remove(deps.storage, &collection);
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_remove_collection")
+        .add_attribute("collection", collection)
+    )
+}
+
+pub fn execute_propose_new_admin(deps: DepsMut, info: MessageInfo, new_admin: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    PENDING_ADMIN.save(deps.storage, &new_admin)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_propose_new_admin")
+        .add_attribute("admin", info.sender)
+        .add_attribute("new_admin", new_admin)
+    )
+}
+
+pub fn execute_accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let new_admin = PENDING_ADMIN.may_load(deps.storage)?.ok_or(ContractError::NoPendingAdmin {})?;
+    if new_admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut config = CONFIG.load(deps.storage)?;
+    config.owner = Some(new_admin.clone());
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_accept_admin")
+        .add_attribute("admin", new_admin)
+    )
+}
+
+pub fn execute_renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    config.owner = None;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_renounce_admin")
+        .add_attribute("admin", info.sender)
+    )
+}
+
+pub fn execute_set_pauser(deps: DepsMut, info: MessageInfo, pauser: Option<String>) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    let res = Response::new().add_attribute("method", "execute_set_pauser");
+    match pauser {
+        Some(pauser) => {
+            let pauser = deps.api.addr_validate(&pauser)?;
+            PAUSER.save(deps.storage, &pauser)?;
+            Ok(res.add_attribute("pauser", pauser))
+        }
+        None => {
+            PAUSER.remove(deps.storage);
+            Ok(res)
+        }
+    }
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    deposits: bool,
+    withdrawals: bool,
+    vault_creation: bool,
+    until: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let is_admin = assert_admin(&config, &info.sender).is_ok();
+    if !is_admin && PAUSER.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if until.is_some_and(|until| until.is_expired(&env.block)) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+    // pausing nothing would still start max pause duration
+    if !deposits && !withdrawals && !vault_creation {
+        return Err(ContractError::InvalidPause {});
+    }
+
+    // expired pause is forgotten
+    let active = PAUSE.may_load(deps.storage)?.filter(|pause| pause.is_active(&env.block));
+    let mut pause = active.clone().unwrap_or_default();
+    pause.deposits |= deposits;
+    pause.withdrawals |= withdrawals;
+    pause.vault_creation |= vault_creation;
+    pause.until = match active {
+        // shortening a pause unpauses early, which only admin can do
+        Some(active) if !is_admin => later_until(active.until, until),
+        _ => until,
+    };
+    if pause.paused_at.is_none() {
+        pause.paused_at = Some(Activity { height: env.block.height, time: env.block.time });
+    }
+    PAUSE.save(deps.storage, &pause)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_pause")
+        .add_attribute("sender", info.sender)
+        .add_attribute("deposits", pause.deposits.to_string())
+        .add_attribute("withdrawals", pause.withdrawals.to_string())
+        .add_attribute("vault_creation", pause.vault_creation.to_string())
+    )
+}
+
+// later end of two pauses. pause without until doesn't end
+fn later_until(a: Option<Expiration>, b: Option<Expiration>) -> Option<Expiration> {
+    match (a, b) {
+        (Some(a), Some(b)) if a.partial_cmp(&b) == Some(Ordering::Less) => Some(b),
+        (Some(a), Some(_)) => Some(a),
+        _ => None,
+    }
+}
+
+pub fn execute_unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    PAUSE.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "execute_unpause"))
+}
+
+pub fn execute_start_wind_down(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if WIND_DOWN.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::WindingDown {});
+    }
+    // users can retire contract themselves if admin left withdrawals paused
+    if assert_admin(&config, &info.sender).is_err() {
+        let pause = PAUSE.may_load(deps.storage)?.filter(|pause| pause.is_active(&env.block) && pause.withdrawals);
+        let paused_too_long = match (pause.and_then(|pause| pause.paused_at), config.max_pause_duration) {
+            (Some(paused_at), Some(max_pause_duration)) => paused_at.expires(&max_pause_duration).is_expired(&env.block),
+            _ => false,
+        };
+        if !paused_too_long {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+
+    let starts_at = config.wind_down_delay.unwrap_or(Duration::Time(0)).after(&env.block);
+    WIND_DOWN.save(deps.storage, &WindDown { starts_at, triggered_by: info.sender.clone() })?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_start_wind_down")
+        .add_attribute("triggered_by", info.sender)
+        .add_attribute("starts_at", starts_at.to_string())
+    )
+}
+
+pub fn execute_cancel_wind_down(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_admin(&config, &info.sender)?;
+    let wind_down = WIND_DOWN.may_load(deps.storage)?.ok_or(ContractError::NotWindingDown {})?;
+    if wind_down.is_active(&env.block) {
+        return Err(ContractError::WindingDown {});
+    }
+    WIND_DOWN.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "execute_cancel_wind_down"))
+}
+
+// withdraw everything in vaults of sender. pause and unbonding period don't apply
+pub fn execute_emergency_exit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    if !is_winding_down(deps.storage, &env.block)? {
+        return Err(ContractError::NotWindingDown {});
+    }
+
+    let vault_ids = VAULTS
+        .prefix(&info.sender)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    // one transfer per token
+    let mut amounts: Vec<(AssetInfo, Uint128)> = vec![];
+    let mut nft_msgs = vec![];
+    for vault_id in vault_ids {
+        let tokens = BALANCES
+            .prefix((&info.sender, vault_id))
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, balance)| balance.token))
+            .collect::<StdResult<Vec<_>>>()?;
+        for token in tokens {
+            let balance = load_balance(deps.storage, info.sender.clone(), vault_id, token.clone(), &env.block)?;
+            if balance.collected.is_zero() {
+                continue;
+            }
+            withdraw_vault(deps.storage, &env.block, info.sender.clone(), vault_id, token.clone(), balance.collected)?;
+            match amounts.iter_mut().find(|(t, _)| *t == token) {
+                Some((_, amount)) => *amount += balance.collected,
+                None => amounts.push((token, balance.collected)),
+            }
+        }
+
+        let nfts = NFTS
+            .sub_prefix((&info.sender, vault_id))
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (collection, token_id) in nfts {
+            NFTS.remove(deps.storage, ((&info.sender, vault_id), &collection, &token_id));
+            nft_msgs.push(WasmMsg::Execute {
+                contract_addr: collection.to_string(),
+                msg: to_json_binary(&Cw721ExecuteMsg::TransferNft { recipient: info.sender.to_string(), token_id })?,
+                funds: vec![],
+            });
+        }
+    }
+    if amounts.is_empty() && nft_msgs.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let mut res = Response::new()
+        .add_attribute("method", "execute_emergency_exit")
+        .add_attribute("owner", info.sender.clone())
+        .add_attribute("nfts", nft_msgs.len().to_string());
+    for (token, amount) in amounts {
+        let msg = token.transfer_msg(&info.sender, amount)?;
+        res = res
+            .add_attribute("token", token.to_string())
+            .add_attribute("amount", amount)
+            .add_message(msg);
+    }
+    Ok(res.add_messages(nft_msgs))
+}
+
+pub fn is_winding_down(storage: &dyn Storage, block: &BlockInfo) -> StdResult<bool> {
+    Ok(WIND_DOWN.may_load(storage)?.is_some_and(|wind_down| wind_down.is_active(block)))
+}
+
+pub fn assert_not_paused(storage: &dyn Storage, block: &BlockInfo, paused: fn(&Pause) -> bool) -> Result<(), ContractError> {
+    match PAUSE.may_load(storage)? {
+        Some(pause) if pause.is_active(block) && paused(&pause) => Err(ContractError::Paused {}),
+        _ => Ok(()),
+    }
+}
+
+pub fn assert_admin(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if config.owner.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn validate_asset(api: &dyn Api, token: &AssetInfo) -> StdResult<AssetInfo> {
+    match token {
+        AssetInfo::Cw20(addr) => Ok(AssetInfo::Cw20(api.addr_validate(addr.as_str())?)),
+        AssetInfo::Native(denom) if denom.is_empty() => Err(StdError::generic_err("empty denom")),
+        AssetInfo::Native(denom) => Ok(AssetInfo::Native(denom.clone())),
+    }
+}
+
+// check unlock_at is between min and max lock duration from now.
+// time based and block based values can't be compared, so mixing them is out of bounds
+pub fn is_lock_in_bounds(config: &Config, block: &BlockInfo, unlock_at: &Expiration) -> bool {
+    if let Some(min) = config.min_lock_duration {
+        if !matches!(min.after(block).partial_cmp(unlock_at), Some(Ordering::Less | Ordering::Equal)) {
+            return false;
+        }
+    }
+    if let Some(max) = config.max_lock_duration {
+        if !matches!(unlock_at.partial_cmp(&max.after(block)), Some(Ordering::Less | Ordering::Equal)) {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn validate_penalty(penalty: &Option<EarlyWithdrawPenalty>) -> Result<(), ContractError> {
+    match penalty {
+        Some(penalty) if penalty.rate > Decimal::one() => Err(ContractError::InvalidPenaltyRate {}),
+        _ => Ok(()),
+    }
+}
+
+pub fn load_vault(storage: &dyn Storage, owner: &Addr, vault_id: u64) -> Result<Vault, ContractError> {
+    VAULTS
+        .may_load(storage, (owner, vault_id))?
+        .ok_or(ContractError::VaultNotFound {})
+}
+
+pub fn deposit_vault(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    vault: &Vault,
+    token: AssetInfo,
+    ledger: Ledger,
+) -> Result<(), ContractError> {
+    if let Some(depositor) = &ledger.depositor {
+        record_deposit(storage, &Deposit {
+            depositor: depositor.clone(),
+            owner_addr: vault.owner_addr.clone(),
+            vault_id: vault.vault_id,
+            token: token.clone(),
+            amount: ledger.coin_amount,
+            memo: ledger.memo.clone(),
+            reference: ledger.reference.clone(),
+            receive_time: ledger.receive_time,
+        })?;
+    }
+    let mut balance = load_balance(storage, vault.owner_addr.clone(), vault.vault_id, token, block)?;
+    push_ledger(storage, &mut balance, ledger)?;
+    save_balance(storage, &balance)?;
+    Ok(())
+}
+
+// keep deposit for lookups by depositor and payment reference
+pub fn record_deposit(storage: &mut dyn Storage, deposit: &Deposit) -> StdResult<()> {
+    let id = DEPOSIT_COUNT.may_load(storage)?.unwrap_or_default();
+    DEPOSIT_COUNT.save(storage, &(id + 1))?;
+    if let Some(reference) = &deposit.reference {
+        REFERENCES.save(storage, reference, &(deposit.depositor.clone(), id))?;
+    }
+    DEPOSITS.save(storage, (&deposit.depositor, id), deposit)
+}
+
+// memo and payment reference of ReceiveMsg. reference can be used only once
+pub fn assert_valid_memo(storage: &dyn Storage, msg: &ReceiveMsg) -> Result<(), ContractError> {
+    if let Some(memo) = &msg.memo {
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(ContractError::InvalidMemo {});
+        }
+    }
+    if let Some(reference) = &msg.reference {
+        if reference.is_empty() || reference.len() > MAX_REFERENCE_LEN {
+            return Err(ContractError::InvalidMemo {});
+        }
+        if REFERENCES.has(storage, reference) {
+            return Err(ContractError::ReferenceAlreadyUsed { reference: reference.clone() });
+        }
+    }
+    Ok(())
+}
+
+// balance of token in vault. None if vault doesn't exist, empty balance if vault doesn't hold the token
+pub fn read_balance(
+    storage: &dyn Storage,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+) -> StdResult<Option<VaultBalance>> {
+    if !VAULTS.has(storage, (&addr, vault_id)) {
+        return Ok(None);
+    }
+    if let Some(balance) = BALANCES.may_load(storage, ((&addr, vault_id), &token.key()))? {
+        return Ok(Some(balance));
+    }
+    // vault doesn't get penalty redistributed before it holds the token
+    let penalty_index = PENALTY_INDEX.may_load(storage, &token.key())?.unwrap_or_default();
+    Ok(Some(VaultBalance {
+        owner_addr: addr,
+        vault_id,
+        token,
+        collected: Uint128::zero(),
+        claimed: Uint128::zero(),
+        next_ledger_id: 0,
+        ledger_cursor: 0,
+        penalty_index,
+    }))
+}
+
+// load balance with redistributed penalty added since last update
+pub fn load_balance(
+    storage: &mut dyn Storage,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    block: &BlockInfo,
+) -> Result<VaultBalance, ContractError> {
+    let mut balance = read_balance(storage, addr, vault_id, token)?.ok_or(ContractError::VaultNotFound {})?;
+    if let Some(ledger) = penalty_share(storage, &balance, block)? {
+        push_ledger(storage, &mut balance, ledger)?;
+    }
+    balance.penalty_index = PENALTY_INDEX.may_load(storage, &balance.token.key())?.unwrap_or_default();
+    Ok(balance)
+}
+
+// redistributed penalty not added to vault yet.
+// it is added as a deposit which can be withdrawn immediately
+pub fn penalty_share(storage: &dyn Storage, balance: &VaultBalance, block: &BlockInfo) -> StdResult<Option<Ledger>> {
+    let index = PENALTY_INDEX.may_load(storage, &balance.token.key())?.unwrap_or_default();
+    let share = balance.collected * (index - balance.penalty_index);
+    if share.is_zero() {
+        return Ok(None);
+    }
+    Ok(Some(Ledger {
+        coin_amount: share,
+        claimed_amount: Uint128::zero(),
+        receive_time: block.time,
+        unlock_at: Expiration::AtTime(block.time),
+        vesting: None,
+        depositor: None,
+        memo: None,
+        reference: None,
+    }))
+}
+
+// save balance and keep TOTAL_COLLECTED and EXCLUDED_COLLECTED of token in sync with collected coins of vault
+pub fn save_balance(storage: &mut dyn Storage, balance: &VaultBalance) -> StdResult<()> {
+    let token_key = balance.token.key();
+    let key = ((&balance.owner_addr, balance.vault_id), token_key.as_str());
+    let prev_collected = BALANCES.may_load(storage, key)?.map(|b| b.collected).unwrap_or_default();
+    let total_collected = TOTAL_COLLECTED.may_load(storage, &token_key)?.unwrap_or_default();
+    TOTAL_COLLECTED.save(storage, &token_key, &(total_collected + balance.collected - prev_collected))?;
+    if is_contract_vault(storage, balance)? {
+        let excluded = EXCLUDED_COLLECTED.may_load(storage, &token_key)?.unwrap_or_default();
+        EXCLUDED_COLLECTED.save(storage, &token_key, &(excluded + balance.collected - prev_collected))?;
+    }
+    // every deposit and withdraw changes collected coins here, so rewards are settled before it
+    settle_rewards(storage, balance, prev_collected)?;
+    BALANCES.save(storage, key, balance)
+}
+
+// multisig and pooled vaults are owned by this contract, and don't earn rewards
+pub fn is_contract_vault(storage: &dyn Storage, balance: &VaultBalance) -> StdResult<bool> {
+    Ok(CONTRACT_ADDR.may_load(storage)?.as_ref() == Some(&balance.owner_addr))
+}
+
+// rewards of vault accrued on collected coins since its checkpoints, with the current index per reward token.
+// vault without checkpoint held collected coins since the first rewards of the reward token
+pub fn accrued_rewards(
+    storage: &dyn Storage,
+    balance: &VaultBalance,
+    collected: Uint128,
+) -> StdResult<Vec<(Addr, Decimal, Uint128)>> {
+    if is_contract_vault(storage, balance)? {
+        return Ok(vec![]);
+    }
+    let token_key = balance.token.key();
+    let indexes = REWARD_INDEX
+        .prefix(&token_key)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    indexes
+        .into_iter()
+        .map(|(reward_token, index)| {
+            let key = ((&balance.owner_addr, balance.vault_id), token_key.as_str(), &reward_token);
+            let checkpoint = REWARD_CHECKPOINTS.may_load(storage, key)?.unwrap_or_default();
+            Ok((reward_token, index, collected * (index - checkpoint)))
+        })
+        .collect()
+}
+
+// add rewards accrued on collected coins before the change to rewards of vault owner, and move checkpoints
+pub fn settle_rewards(storage: &mut dyn Storage, balance: &VaultBalance, prev_collected: Uint128) -> StdResult<()> {
+    let token_key = balance.token.key();
+    for (reward_token, index, accrued) in accrued_rewards(storage, balance, prev_collected)? {
+        if !accrued.is_zero() {
+            REWARDS.update(storage, (&balance.owner_addr, &reward_token), |rewards| -> StdResult<_> {
+                Ok(rewards.unwrap_or_default() + accrued)
+            })?;
+        }
+        let key = ((&balance.owner_addr, balance.vault_id), token_key.as_str(), &reward_token);
+        if balance.collected.is_zero() {
+            REWARD_CHECKPOINTS.remove(storage, key);
+        } else {
+            REWARD_CHECKPOINTS.save(storage, key, &index)?;
+        }
+    }
+    Ok(())
+}
+
+// record a new deposit. only the new entry is written, so deposit cost doesn't grow with ledger size
+pub fn push_ledger(storage: &mut dyn Storage, balance: &mut VaultBalance, ledger: Ledger) -> StdResult<()> {
+    LEDGERS.save(storage, ((&balance.owner_addr, balance.vault_id), balance.token.key().as_str(), balance.next_ledger_id), &ledger)?;
+    let (index, unlock) = unlock_index(&ledger.unlock_at);
+    index.save(storage, ((&balance.owner_addr, balance.vault_id), balance.token.key().as_str(), (unlock, balance.next_ledger_id)), &())?;
+    balance.next_ledger_id += 1;
+    balance.collected += ledger.coin_amount;
+    Ok(())
+}
+
+// deposits of token in vault in order of deposit, from the oldest one not fully withdrawn.
+// withdrawals use the unlock index instead, so this is only for listing all deposits, like moving a vault
+pub fn ledger_entries<'a>(
+    storage: &'a dyn Storage,
+    balance: &VaultBalance,
+) -> Box<dyn Iterator<Item = StdResult<(u64, Ledger)>> + 'a> {
+    LEDGERS
+        .prefix(((&balance.owner_addr, balance.vault_id), balance.token.key().as_str()))
+        .range(storage, Some(Bound::inclusive(balance.ledger_cursor)), None, Order::Ascending)
+}
+
+// unlock index of deposit and its position there. deposit locked forever goes after all unlock times
+fn unlock_index<'a>(unlock_at: &Expiration) -> (UnlockIndex<'a>, u64) {
+    match unlock_at {
+        Expiration::AtHeight(height) => (UNLOCK_HEIGHTS, *height),
+        Expiration::AtTime(time) => (UNLOCK_TIMES, time.nanos()),
+        Expiration::Never {} => (UNLOCK_TIMES, u64::MAX),
+    }
+}
+
+fn remove_unlock(storage: &mut dyn Storage, balance: &VaultBalance, id: u64, ledger: &Ledger) {
+    let (index, unlock) = unlock_index(&ledger.unlock_at);
+    index.remove(storage, ((&balance.owner_addr, balance.vault_id), balance.token.key().as_str(), (unlock, id)));
+}
+
+// deposits of token in vault unlocked at block, including vesting deposits being released,
+// in order of unlock time and then unlock height. locked deposits are not iterated
+pub fn unlocked_entries<'a>(
+    storage: &'a dyn Storage,
+    balance: &VaultBalance,
+    block: &BlockInfo,
+) -> Box<dyn Iterator<Item = StdResult<(u64, Ledger)>> + 'a> {
+    let times = Bound::inclusive((block.time.nanos(), u64::MAX));
+    let heights = Bound::inclusive((block.height, u64::MAX));
+    indexed_entries(storage, balance, (None, Some(times)), (None, Some(heights)))
+}
+
+// deposits of token in vault still locked at block in order of unlock time and then unlock height
+pub fn locked_entries<'a>(
+    storage: &'a dyn Storage,
+    balance: &VaultBalance,
+    block: &BlockInfo,
+) -> Box<dyn Iterator<Item = StdResult<(u64, Ledger)>> + 'a> {
+    let times = Bound::exclusive((block.time.nanos(), u64::MAX));
+    let heights = Bound::exclusive((block.height, u64::MAX));
+    indexed_entries(storage, balance, (Some(times), None), (Some(heights), None))
+}
+
+type UnlockRange<'a> = (Option<Bound<'a, (u64, u64)>>, Option<Bound<'a, (u64, u64)>>);
+
+fn indexed_entries<'a>(
+    storage: &'a dyn Storage,
+    balance: &VaultBalance,
+    times: UnlockRange,
+    heights: UnlockRange,
+) -> Box<dyn Iterator<Item = StdResult<(u64, Ledger)>> + 'a> {
+    let token_key = balance.token.key();
+    let prefix = ((&balance.owner_addr, balance.vault_id), token_key.as_str());
+    let ids = UNLOCK_TIMES
+        .prefix(prefix)
+        .keys(storage, times.0, times.1, Order::Ascending)
+        .chain(UNLOCK_HEIGHTS.prefix(prefix).keys(storage, heights.0, heights.1, Order::Ascending));
+    let (owner, vault_id) = (balance.owner_addr.clone(), balance.vault_id);
+    Box::new(ids.map(move |key| {
+        let (_, id) = key?;
+        Ok((id, LEDGERS.load(storage, ((&owner, vault_id), token_key.as_str(), id))?))
+    }))
+}
+
+// save changed deposits, removing the empty ones, and move cursor to the oldest remaining deposit for ledger_entries
+pub fn update_ledger(
+    storage: &mut dyn Storage,
+    balance: &mut VaultBalance,
+    changed: Vec<(u64, Ledger)>,
+) -> StdResult<()> {
+    for (id, ledger) in changed {
+        if ledger.coin_amount.is_zero() {
+            LEDGERS.remove(storage, ((&balance.owner_addr, balance.vault_id), balance.token.key().as_str(), id));
+            remove_unlock(storage, balance, id, &ledger);
+        } else {
+            LEDGERS.save(storage, ((&balance.owner_addr, balance.vault_id), balance.token.key().as_str(), id), &ledger)?;
+        }
+    }
+    balance.ledger_cursor = LEDGERS
+        .prefix(((&balance.owner_addr, balance.vault_id), balance.token.key().as_str()))
+        .keys(storage, Some(Bound::inclusive(balance.ledger_cursor)), None, Order::Ascending)
+        .next()
+        .transpose()?
+        .unwrap_or(balance.next_ledger_id);
+    Ok(())
+}
+
+// iterate and withdraw coin from unlocked deposits in order of unlock time.
+// vesting deposit can be released only partly, so take only the withdrawable part.
+// returns withdrawn amount, which is less than amount if not enough coins are withdrawable,
+// and the changed deposits. iteration stops as soon as amount is withdrawn
+pub fn withdraw_from_ledger(
+    entries: impl Iterator<Item = StdResult<(u64, Ledger)>>,
+    block: &BlockInfo,
+    amount: Uint128,
+) -> StdResult<(Uint128, Vec<(u64, Ledger)>)> {
+    let mut amount_sum = Uint128::new(0);
+    let mut changed = vec![];
+    for entry in entries {
+        if amount_sum == amount {
+            break;
+        }
+        let (id, mut l) = entry?;
+        let withdrawable = l.withdrawable_amount(block);
+        if withdrawable.is_zero() {
+            continue;
+        }
+        let take = withdrawable.min(amount - amount_sum);
+        l.withdraw(take);
+        amount_sum += take;
+        changed.push((id, l));
+    }
+    Ok((amount_sum, changed))
+}
+
+// withdraw unlocked coins first, then locked coins in order of unlock time.
+// unlocked deposits are all visited before locked coins are taken, and then only vesting deposits
+// among them keep locked coins, which are taken before locked deposits.
+// returns withdrawn amount, penalty for the locked coins and the changed deposits
+#[allow(clippy::type_complexity)]
+pub fn early_withdraw_from_ledger(
+    unlocked: impl Iterator<Item = StdResult<(u64, Ledger)>>,
+    mut locked: impl Iterator<Item = StdResult<(u64, Ledger)>>,
+    block: &BlockInfo,
+    amount: Uint128,
+    penalty: &EarlyWithdrawPenalty,
+) -> StdResult<(Uint128, Uint128, Vec<(u64, Ledger)>)> {
+    let mut amount_sum = Uint128::new(0);
+    let mut penalty_sum = Uint128::zero();
+    let mut entries = vec![];
+    for entry in unlocked {
+        if amount_sum == amount {
+            break;
+        }
+        let (id, mut l) = entry?;
+        let take = l.withdrawable_amount(block).min(amount - amount_sum);
+        l.withdraw(take);
+        amount_sum += take;
+        entries.push((id, l, !take.is_zero()));
+    }
+    let mut i = 0;
+    while amount_sum < amount {
+        if i == entries.len() {
+            match locked.next() {
+                Some(entry) => {
+                    let (id, l) = entry?;
+                    entries.push((id, l, false));
+                }
+                None => break,
+            }
+        }
+        let (_, l, changed) = &mut entries[i];
+        let take = l.coin_amount.min(amount - amount_sum);
+        if !take.is_zero() {
+            penalty_sum += l.early_withdraw_penalty(penalty, block, take);
+            l.withdraw(take);
+            amount_sum += take;
+            *changed = true;
+        }
+        i += 1;
+    }
+    let changed = entries
+        .into_iter()
+        .filter(|(_, _, c)| *c)
+        .map(|(id, l, _)| (id, l))
+        .collect();
+    Ok((amount_sum, penalty_sum, changed))
+}
+
+// error for withdrawing more than withdrawable coins.
+// if vault has enough coins but some are locked, tell when the deposit unlocked first is fully unlocked.
+// only vesting deposits among unlocked deposits and the first locked deposits of each unlock index are looked at
+pub fn withdraw_error(
+    storage: &dyn Storage,
+    balance: &VaultBalance,
+    block: &BlockInfo,
+    available: Uint128,
+    requested: Uint128,
+) -> ContractError {
+    if requested > balance.collected {
+        return ContractError::InsufficientFunds { available, requested };
+    }
+    let mut unlock_at: Option<Expiration> = None;
+    let token_key = balance.token.key();
+    let prefix = ((&balance.owner_addr, balance.vault_id), token_key.as_str());
+    let first_locked = |index: UnlockIndex, unlock: u64| {
+        index
+            .prefix(prefix)
+            .keys(storage, Some(Bound::exclusive((unlock, u64::MAX))), None, Order::Ascending)
+            .next()
+            .map(|key| key.and_then(|(_, id)| Ok((id, LEDGERS.load(storage, (prefix.0, prefix.1, id))?))))
+    };
+    let entries = unlocked_entries(storage, balance, block)
+        .filter(|entry| entry.as_ref().map_or(true, |(_, l)| l.vesting.is_some()))
+        .chain(first_locked(UNLOCK_TIMES, block.time.nanos()))
+        .chain(first_locked(UNLOCK_HEIGHTS, block.height));
+    for entry in entries {
+        let (_, l) = match entry {
+            Ok(entry) => entry,
+            Err(err) => return err.into(),
+        };
+        let entry_unlock_at = l.fully_unlocked_at();
+        if l.coin_amount > l.withdrawable_amount(block) && unlock_at.is_none_or(|u| entry_unlock_at < u) {
+            unlock_at = Some(entry_unlock_at);
+        }
+    }
+    match unlock_at {
+        Some(unlock_at) => ContractError::FundsLocked { unlock_at },
+        None => ContractError::InsufficientFunds { available, requested },
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetVault { vault_owner_addr: vault_admin_addr, vault_id, token } => {
+            to_json_binary(&query_vault(deps, env, vault_admin_addr, vault_id, token)?)
+        }
+        QueryMsg::Balances { vault_owner_addr, vault_id } => {
+            to_json_binary(&query_balances(deps, env, vault_owner_addr, vault_id)?)
+        }
+        QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::PauseStatus {} => to_json_binary(&query_pause_status(deps, env)?),
+        QueryMsg::WindDownStatus {} => {
+            let wind_down = WIND_DOWN.may_load(deps.storage)?;
+            to_json_binary(&WindDownStatusResponse {
+                active: wind_down.as_ref().is_some_and(|wind_down| wind_down.is_active(&env.block)),
+                wind_down,
+            })
+        }
+        QueryMsg::Tokens {} => to_json_binary(&query_tokens(deps)?),
+        QueryMsg::Collections {} => {
+            let collections = COLLECTIONS.keys(deps.storage, None, None, Order::Ascending).collect::<StdResult<_>>()?;
+            to_json_binary(&CollectionsResponse { collections })
+        }
+        QueryMsg::Claims { address, start_after, limit } => {
+            to_json_binary(&query_claims(deps, env, address, start_after, limit)?)
+        }
+        QueryMsg::PendingRewards { owner, vault_id } => to_json_binary(&query_pending_rewards(deps, owner, vault_id)?),
+        QueryMsg::EarlyWithdrawQuote { vault_owner_addr, vault_id, token, amount } => {
+            to_json_binary(&query_early_withdraw_quote(deps, env, vault_owner_addr, vault_id, token, amount)?)
+        }
+        QueryMsg::LedgerEntries { owner, vault_id, token, start_after, limit } => {
+            to_json_binary(&query_ledger_entries(deps, owner, vault_id, token, start_after, limit)?)
+        }
+        QueryMsg::DepositByReference { reference } => {
+            let (depositor, id) = REFERENCES.load(deps.storage, &reference)?;
+            to_json_binary(&DepositInfo { id, deposit: DEPOSITS.load(deps.storage, (&depositor, id))? })
+        }
+        QueryMsg::DepositsByDepositor { depositor, start_after, limit } => {
+            to_json_binary(&query_deposits_by_depositor(deps, depositor, start_after, limit)?)
+        }
+        QueryMsg::ListVaults { start_after, limit } => to_json_binary(&query_list_vaults(deps, env, start_after, limit)?),
+        QueryMsg::VaultsByOwner { owner, start_after, limit } => {
+            to_json_binary(&query_vaults_by_owner(deps, owner, start_after, limit)?)
+        }
+        QueryMsg::WithdrawableAmount { owner, vault_id, token, at_time, limit } => {
+            to_json_binary(&query_withdrawable_amount(deps, env, owner, vault_id, token, at_time, limit)?)
+        }
+        QueryMsg::SimulateWithdraw { owner, vault_id, token, amount } => {
+            to_json_binary(&query_simulate_withdraw(deps, env, owner, vault_id, token, amount)?)
+        }
+        QueryMsg::Nfts { vault_owner_addr, vault_id, start_after, limit } => {
+            to_json_binary(&query_nfts(deps, vault_owner_addr, vault_id, start_after, limit)?)
+        }
+        QueryMsg::VaultTransfer { vault_owner_addr, vault_id } => to_json_binary(&VaultTransferResponse {
+            transfer: PENDING_TRANSFERS.may_load(deps.storage, (&vault_owner_addr, vault_id))?,
+        }),
+        QueryMsg::DepositPolicy { owner, vault_id, start_after, limit } => {
+            to_json_binary(&query_deposit_policy(deps, owner, vault_id, start_after, limit)?)
+        }
+        QueryMsg::Allowances { owner, vault_id, start_after, limit } => {
+            to_json_binary(&query_allowances(deps, owner, vault_id, start_after, limit)?)
+        }
+        QueryMsg::TotalAssets { vault_id } => {
+            let (pool, total_assets) = load_pool_assets(deps, &env, vault_id)?;
+            to_json_binary(&TotalAssetsResponse { token: pool.token, total_assets, total_shares: pool.total_shares })
+        }
+        QueryMsg::ConvertToShares { vault_id, assets } | QueryMsg::PreviewDeposit { vault_id, assets } => {
+            let (pool, total_assets) = load_pool_assets(deps, &env, vault_id)?;
+            to_json_binary(&SharesResponse { shares: pool.to_shares(assets, total_assets) })
+        }
+        QueryMsg::ConvertToAssets { vault_id, shares } | QueryMsg::PreviewRedeem { vault_id, shares } => {
+            let (pool, total_assets) = load_pool_assets(deps, &env, vault_id)?;
+            to_json_binary(&AssetsResponse { assets: pool.to_assets(shares, total_assets) })
+        }
+        QueryMsg::PoolShare { vault_id, owner } => {
+            let (pool, total_assets) = load_pool_assets(deps, &env, vault_id)?;
+            let holder = POOL_SHARES.may_load(deps.storage, (vault_id, &owner))?;
+            let shares = holder.as_ref().map(|holder| holder.shares).unwrap_or_default();
+            to_json_binary(&PoolShareResponse {
+                shares,
+                assets: pool.to_assets(shares, total_assets),
+                unlock_at: holder.map(|holder| holder.unlock_at),
+            })
+        }
+        QueryMsg::Recovery { owner, vault_id } => to_json_binary(&RecoveryResponse {
+            guardians: GUARDIANS.may_load(deps.storage, (&owner, vault_id))?,
+            recovery: RECOVERIES.may_load(deps.storage, (&owner, vault_id))?,
+        }),
+        QueryMsg::Inheritance { owner, vault_id } => {
+            to_json_binary(&query_inheritance(deps, env, owner, vault_id)?)
+        }
+        QueryMsg::LastActivity { owner } => to_json_binary(&LastActivityResponse {
+            last_activity: LAST_ACTIVITY.may_load(deps.storage, &owner)?,
+        }),
+        QueryMsg::Proposal { proposal_id } => {
+            let proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+            to_json_binary(&proposal_response(&env.block, proposal_id, proposal))
+        }
+        QueryMsg::ListProposals { start_after, limit } => {
+            to_json_binary(&query_list_proposals(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Voters { vault_id, start_after, limit } => {
+            to_json_binary(&query_voters(deps, vault_id, start_after, limit)?)
+        }
+    }
+}
+
+// settled rewards of owner and rewards accrued since the last change of vault
+fn query_pending_rewards(deps: Deps, owner: Addr, vault_id: u64) -> StdResult<PendingRewardsResponse> {
+    let mut rewards = REWARDS
+        .prefix(&owner)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for balance in BALANCES.prefix((&owner, vault_id)).range(deps.storage, None, None, Order::Ascending) {
+        let (_, balance) = balance?;
+        for (reward_token, _, accrued) in accrued_rewards(deps.storage, &balance, balance.collected)? {
+            match rewards.iter_mut().find(|(token, _)| *token == reward_token) {
+                Some((_, amount)) => *amount += accrued,
+                None => rewards.push((reward_token, accrued)),
+            }
+        }
+    }
+    Ok(PendingRewardsResponse {
+        rewards: rewards
+            .into_iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(reward_token, amount)| Asset { info: AssetInfo::Cw20(reward_token), amount })
+            .collect(),
+    })
+}
+
+fn load_pool_assets(deps: Deps, env: &Env, vault_id: u64) -> StdResult<(Pool, Uint128)> {
+    let pool = POOLS.load(deps.storage, vault_id)?;
+    let total_assets = pool_total_assets(deps.storage, &env.block, &env.contract.address, vault_id, &pool)?;
+    Ok((pool, total_assets))
+}
+
+fn query_inheritance(deps: Deps, env: Env, owner: Addr, vault_id: u64) -> StdResult<InheritanceResponse> {
+    let inheritance = INHERITANCES
+        .may_load(deps.storage, (&owner, vault_id))?
+        .ok_or_else(|| StdError::not_found("inheritance"))?;
+    let tokens = BALANCES
+        .prefix((&owner, vault_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, balance)| balance.token))
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut beneficiaries = vec![];
+    for beneficiary in inheritance.beneficiaries {
+        let mut claimed = vec![];
+        for token in &tokens {
+            let key = ((&owner, vault_id), &beneficiary.addr, token.key());
+            if let Some(amount) = INHERITED.may_load(deps.storage, (key.0, key.1, key.2.as_str()))? {
+                claimed.push(Asset { info: token.clone(), amount });
+            }
+        }
+        beneficiaries.push(BeneficiaryInfo { addr: beneficiary.addr, share: beneficiary.share, claimed });
+    }
+    let last_activity = LAST_ACTIVITY.may_load(deps.storage, &owner)?;
+    let claimable_at = last_activity.as_ref().map(|activity| activity.expires(&inheritance.inactivity_period));
+    Ok(InheritanceResponse {
+        beneficiaries,
+        inactivity_period: inheritance.inactivity_period,
+        claimable: claimable_at.is_some_and(|claimable_at| claimable_at.is_expired(&env.block)),
+        last_activity,
+        claimable_at,
+    })
+}
+
+fn proposal_response(block: &BlockInfo, id: u64, proposal: Proposal) -> ProposalResponse {
+    ProposalResponse {
+        id,
+        vault_id: proposal.vault_id,
+        status: proposal.current_status(block),
+        title: proposal.title,
+        description: proposal.description,
+        action: proposal.action,
+        expires: proposal.expires,
+        threshold: ThresholdResponse::AbsoluteCount {
+            weight: proposal.threshold,
+            total_weight: proposal.total_weight,
+        },
+        proposer: proposal.proposer,
+    }
+}
+
+fn query_list_proposals(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let proposals = PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, proposal)| proposal_response(&env.block, id, proposal)))
+        .collect::<StdResult<_>>()?;
+    Ok(ProposalListResponse { proposals })
+}
+
+fn query_voters(
+    deps: Deps,
+    vault_id: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<VoterListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let voters = MEMBERS
+        .prefix(vault_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(addr, weight)| VoterDetail { addr: addr.into_string(), weight }))
+        .collect::<StdResult<_>>()?;
+    Ok(VoterListResponse { voters })
+}
+
+fn query_deposit_policy(
+    deps: Deps,
+    owner: Addr,
+    vault_id: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<DepositPolicyResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let depositors = DEPOSITORS
+        .prefix((&owner, vault_id))
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(DepositPolicyResponse {
+        policy: DEPOSIT_POLICIES.may_load(deps.storage, (&owner, vault_id))?.unwrap_or_default(),
+        depositors,
+    })
+}
+
+fn query_allowances(
+    deps: Deps,
+    owner: Addr,
+    vault_id: u64,
+    start_after: Option<AllowanceId>,
+    limit: Option<u32>,
+) -> StdResult<AllowancesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_key = start_after.as_ref().map(|id| (&id.spender, id.token.key()));
+    let start = start_key.as_ref().map(|(spender, token_key)| Bound::exclusive((*spender, token_key.as_str())));
+    let allowances = ALLOWANCES
+        .sub_prefix((&owner, vault_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|((spender, _), allowance)| AllowanceInfo {
+                spender,
+                token: allowance.token,
+                allowance: allowance.allowance,
+                expires: allowance.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AllowancesResponse { allowances })
+}
+
+fn query_deposits_by_depositor(
+    deps: Deps,
+    depositor: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DepositsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let deposits = DEPOSITS
+        .prefix(&depositor)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, deposit)| DepositInfo { id, deposit }))
+        .collect::<StdResult<_>>()?;
+    Ok(DepositsResponse { deposits })
+}
+
+fn query_nfts(
+    deps: Deps,
+    addr: Addr,
+    vault_id: u64,
+    start_after: Option<NftId>,
+    limit: Option<u32>,
+) -> StdResult<NftsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .as_ref()
+        .map(|id| Bound::exclusive((&id.collection, id.token_id.as_str())));
+    let nfts = NFTS
+        .sub_prefix((&addr, vault_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|((collection, token_id), ledger)| NftInfo { collection, token_id, ledger })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(NftsResponse { nfts })
+}
+
+// evaluated with the same lock rules as Withdraw, at at_time if set
+fn query_withdrawable_amount(
+    deps: Deps,
+    env: Env,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    at_time: Option<Timestamp>,
+    limit: Option<u32>,
+) -> StdResult<WithdrawableAmountResponse> {
+    let mut block = env.block;
+    if let Some(at_time) = at_time {
+        block.time = at_time;
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (balance, share) = read_balance_share(deps.storage, addr, vault_id, token, &block)?;
+    let mut available = Uint128::zero();
+    let mut pending: Vec<(Expiration, Uint128)> = vec![];
+    for entry in share.into_iter().map(Ok).chain(unlocked_entries(deps.storage, &balance, &block)) {
+        let (_, l) = entry?;
+        available += l.withdrawable_amount(&block);
+        pending.extend(l.pending_unlocks(&block));
+    }
+    for entry in locked_entries(deps.storage, &balance, &block).take(limit) {
+        pending.extend(entry?.1.pending_unlocks(&block));
+    }
+    let mut unlocks: Vec<UnlockInfo> = vec![];
+    // height and time can't be compared, so height based unlocks come first
+    pending.sort_by_key(|(unlock_at, _)| match unlock_at {
+        Expiration::AtHeight(height) => (0, *height),
+        Expiration::AtTime(time) => (1, time.nanos()),
+        Expiration::Never {} => (2, 0),
+    });
+    for (unlock_at, amount) in pending {
+        match unlocks.last_mut() {
+            Some(last) if last.unlock_at == unlock_at => last.amount += amount,
+            _ => unlocks.push(UnlockInfo { unlock_at, amount }),
+        }
+    }
+    Ok(WithdrawableAmountResponse {
+        available,
+        locked: balance.collected - available,
+        unlocks,
+    })
+}
+
+fn query_simulate_withdraw(
+    deps: Deps,
+    env: Env,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+) -> StdResult<SimulateWithdrawResponse> {
+    let (balance, share) = read_balance_share(deps.storage, addr, vault_id, token, &env.block)?;
+    let mut before: Vec<(u64, Uint128)> = vec![];
+    let unlocked = share
+        .into_iter()
+        .map(Ok)
+        .chain(unlocked_entries(deps.storage, &balance, &env.block))
+        .inspect(|entry| if let Ok((id, l)) = entry { before.push((*id, l.coin_amount)) });
+    let (amount, changed) = withdraw_from_ledger(unlocked, &env.block, amount)?;
+    let entries = changed
+        .into_iter()
+        .map(|(id, l)| {
+            let prev = before.iter().find(|(prev_id, _)| *prev_id == id).map(|(_, a)| *a).unwrap_or_default();
+            LedgerWithdrawal {
+                id,
+                amount: prev - l.coin_amount,
+                remaining: l.coin_amount,
+            }
+        })
+        .collect();
+    Ok(SimulateWithdrawResponse { amount, entries })
+}
+
+fn query_ledger_entries(
+    deps: Deps,
+    owner: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LedgerEntriesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let entries = LEDGERS
+        .prefix(((&owner, vault_id), token.key().as_str()))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, ledger)| LedgerEntry { id, ledger }))
+        .collect::<StdResult<_>>()?;
+    Ok(LedgerEntriesResponse { entries })
+}
+
+// collected includes redistributed penalty not added to vault yet
+fn query_list_vaults(
+    deps: Deps,
+    env: Env,
+    start_after: Option<VaultRef>,
+    limit: Option<u32>,
+) -> StdResult<ListVaultsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|v| Bound::exclusive((&v.owner, v.vault_id)));
+    let vaults = VAULTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, vault) = item?;
+            let balances = BALANCES
+                .prefix((&vault.owner_addr, vault.vault_id))
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| {
+                    let (_, balance) = item?;
+                    let share = penalty_share(deps.storage, &balance, &env.block)?
+                        .map(|l| l.coin_amount)
+                        .unwrap_or_default();
+                    Ok(Asset {
+                        info: balance.token,
+                        amount: balance.collected + share,
+                    })
+                })
+                .collect::<StdResult<_>>()?;
+            Ok(VaultSummary {
+                owner_addr: vault.owner_addr.to_string(),
+                vault_id: vault.vault_id,
+                label: vault.label,
+                balances,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ListVaultsResponse { vaults })
+}
+
+fn query_vaults_by_owner(
+    deps: Deps,
+    owner: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VaultsByOwnerResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let vaults = VAULTS
+        .prefix(&owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, vault)| vault))
+        .collect::<StdResult<_>>()?;
+    Ok(VaultsByOwnerResponse { vaults })
+}
+
+// amount is capped to coins in vault
+fn query_early_withdraw_quote(
+    deps: Deps,
+    env: Env,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+) -> StdResult<EarlyWithdrawQuoteResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (balance, share) = read_balance_share(deps.storage, addr, vault_id, token, &env.block)?;
+    let unlocked = share.into_iter().map(Ok).chain(unlocked_entries(deps.storage, &balance, &env.block));
+    let (amount, penalty) = match config.early_withdraw_penalty {
+        Some(penalty_config) => {
+            let locked = locked_entries(deps.storage, &balance, &env.block);
+            let (amount, penalty, _) = early_withdraw_from_ledger(unlocked, locked, &env.block, amount, &penalty_config)?;
+            (amount, penalty)
+        }
+        None => (withdraw_from_ledger(unlocked, &env.block, amount)?.0, Uint128::zero()),
+    };
+    Ok(EarlyWithdrawQuoteResponse {
+        amount,
+        penalty,
+        receive_amount: amount - penalty,
+    })
+}
+
+fn query_claims(
+    deps: Deps,
+    env: Env,
+    addr: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let claims = CLAIMS
+        .prefix(&addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, claim)| ClaimInfo { id, claim }))
+        .collect::<StdResult<Vec<_>>>()?;
+    let (matured, pending) = claims
+        .into_iter()
+        .partition(|c| c.claim.release_at.is_expired(&env.block));
+    Ok(ClaimsResponse { pending, matured })
+}
+
+fn query_vault(deps: Deps, env: Env, addr: Addr, vault_id: u64, token: AssetInfo) -> StdResult<VaultResponse> {
+    let (balance, vested) = load_vault_vested(deps.storage, addr.clone(), vault_id, token, &env.block)?;
+    let vault = VAULTS.load(deps.storage, (&addr, vault_id))?;
+    Ok(VaultResponse {
+        owner_addr: balance.owner_addr.to_string(),
+        vault_id,
+        label: vault.label,
+        token: balance.token,
+        collected: balance.collected,
+        vested,
+        claimed: balance.claimed,
+    })
+}
+
+fn query_balances(deps: Deps, env: Env, addr: Addr, vault_id: u64) -> StdResult<BalancesResponse> {
+    if !VAULTS.has(deps.storage, (&addr, vault_id)) {
+        return Err(StdError::not_found("vault"));
+    }
+    let tokens = BALANCES
+        .prefix((&addr, vault_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, balance)| balance.token))
+        .collect::<StdResult<Vec<_>>>()?;
+    let balances = tokens
+        .into_iter()
+        .map(|token| {
+            let (balance, vested) = load_vault_vested(deps.storage, addr.clone(), vault_id, token, &env.block)?;
+            Ok(TokenBalance {
+                token: balance.token,
+                collected: balance.collected,
+                vested,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(BalancesResponse { balances })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner,
+        pending_admin: PENDING_ADMIN.may_load(deps.storage)?,
+        lock_duration: config.lock_duration,
+        min_lock_duration: config.min_lock_duration,
+        max_lock_duration: config.max_lock_duration,
+        unbonding_period: config.unbonding_period,
+        early_withdraw_penalty: config.early_withdraw_penalty,
+        wind_down_delay: config.wind_down_delay,
+        max_pause_duration: config.max_pause_duration,
+        tokens: query_tokens(deps)?.tokens,
+        collections: COLLECTIONS.keys(deps.storage, None, None, Order::Ascending).collect::<StdResult<_>>()?,
+        pauser: PAUSER.may_load(deps.storage)?,
+    })
+}
+
+fn query_pause_status(deps: Deps, env: Env) -> StdResult<PauseStatusResponse> {
+    let pause = PAUSE.may_load(deps.storage)?.filter(|pause| pause.is_active(&env.block)).unwrap_or_default();
+    Ok(PauseStatusResponse {
+        deposits: pause.deposits,
+        withdrawals: pause.withdrawals,
+        vault_creation: pause.vault_creation,
+        until: pause.until,
+        pauser: PAUSER.may_load(deps.storage)?,
+    })
+}
+
+fn query_tokens(deps: Deps) -> StdResult<TokensResponse> {
+    let tokens = TOKENS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, token)| token))
+        .collect::<StdResult<_>>()?;
+    Ok(TokensResponse { tokens })
+}
+
+// balance of token in vault for queries, and redistributed penalty not added yet as a deposit unlocked now
+fn read_balance_share(
+    storage: &dyn Storage,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    block: &BlockInfo,
+) -> StdResult<(VaultBalance, Option<(u64, Ledger)>)> {
+    let mut balance = read_balance(storage, addr, vault_id, token)?.ok_or_else(|| StdError::not_found("vault"))?;
+    let share = penalty_share(storage, &balance, block)?.map(|ledger| (balance.next_ledger_id, ledger));
+    if let Some((_, ledger)) = &share {
+        balance.collected += ledger.coin_amount;
+    }
+    Ok((balance, share))
+}
+
+// balance of token in vault for queries, including redistributed penalty not added yet,
+// and coins which can be withdrawn at block. only unlocked deposits are iterated
+fn load_vault_vested(
+    storage: &dyn Storage,
+    addr: Addr,
+    vault_id: u64,
+    token: AssetInfo,
+    block: &BlockInfo,
+) -> StdResult<(VaultBalance, Uint128)> {
+    let (balance, share) = read_balance_share(storage, addr, vault_id, token, block)?;
+    let vested = share
+        .into_iter()
+        .map(Ok)
+        .chain(unlocked_entries(storage, &balance, block))
+        .map(|entry| entry.map(|(_, l)| l.withdrawable_amount(block)))
+        .sum::<StdResult<Uint128>>()?;
+    Ok((balance, vested))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    CONTRACT_ADDR.save(deps.storage, &env.contract.address)?;
+    // first release kept a single cw20_addr in config, which doesn't parse as current config
+    let mut res = Response::new().add_attribute("method", "migrate");
+    if let Ok(legacy) = LEGACY_CONFIG.load(deps.storage) {
+        res = res.add_attribute("token", AssetInfo::Cw20(legacy.cw20_addr.clone()).to_string());
+        migrate_single_token(deps.storage, legacy)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res)
+}
+
+// move vaults of single token contract to balances of that token in vault 0 of each owner
+fn migrate_single_token(storage: &mut dyn Storage, legacy: LegacyConfig) -> StdResult<()> {
+    let token = AssetInfo::Cw20(legacy.cw20_addr);
+    // first release locked deposits for 60 seconds
+    CONFIG.save(storage, &Config {
+        owner: Some(legacy.owner),
+        lock_duration: Duration::Time(60),
+        min_lock_duration: None,
+        max_lock_duration: None,
+        unbonding_period: None,
+        early_withdraw_penalty: None,
+        wind_down_delay: None,
+        max_pause_duration: None,
+    })?;
+    TOKENS.save(storage, &token.key(), &token)?;
+
+    let vaults = LEGACY_VAULTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (owner, legacy_vault) in vaults {
+        LEGACY_VAULTS.remove(storage, owner.clone());
+        VAULTS.save(storage, (&owner, 0), &Vault {
+            owner_addr: owner.clone(),
+            vault_id: 0,
+            label: None,
+            lock_duration: None,
+        })?;
+        NEXT_VAULT_ID.save(storage, &owner, &1)?;
+        let mut balance = VaultBalance {
+            owner_addr: owner,
+            vault_id: 0,
+            token: token.clone(),
+            collected: Uint128::zero(),
+            claimed: Uint128::zero(),
+            next_ledger_id: 0,
+            ledger_cursor: 0,
+            penalty_index: Decimal::zero(),
+        };
+        for l in legacy_vault.ledger_list {
+            push_ledger(storage, &mut balance, Ledger {
+                coin_amount: l.coin_amount,
+                claimed_amount: Uint128::zero(),
+                receive_time: l.receive_time,
+                // first release kept deposits locked while 60 seconds or less had passed
+                unlock_at: Expiration::AtTime(l.receive_time.plus_seconds(61)),
+                vesting: None,
+                depositor: None,
+                memo: None,
+                reference: None,
+            })?;
+        }
+        save_balance(storage, &balance)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{EarlyWithdrawPenalty, LegacyLedger, LegacyVault, PenaltyDestination, VestingSchedule};
+    use cw_utils::Duration;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{attr, coin, from_json, Addr, BankMsg, CosmosMsg, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+
+    // one cw20 token and 60 seconds lock, other settings unset
+    fn default_instantiate() -> InstantiateMsg {
+        InstantiateMsg {
+            tokens: vec![AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))],
+            lock_duration: Duration::Time(60),
+            min_lock_duration: None,
+            max_lock_duration: None,
+            unbonding_period: None,
+            early_withdraw_penalty: None,
+        }
+    }
+
+    // changes nothing
+    fn default_update_config() -> UpdateConfigMsg {
+        UpdateConfigMsg {
+            lock_duration: None,
+            min_lock_duration: None,
+            max_lock_duration: None,
+            unbonding_period: None,
+            early_withdraw_penalty: None,
+            wind_down_delay: None,
+            max_pause_duration: None,
+            unset: vec![],
+        }
+    }
+
+    // deposit to vault with its default lock
+    fn receive_msg(owner: &str, vault_id: u64) -> ReceiveMsg {
+        ReceiveMsg {
+            vault_owner_addr: Addr::unchecked(owner),
+            vault_id,
+            lock_for: None,
+            unlock_at: None,
+            vesting: None,
+            memo: None,
+            reference: None,
+        }
+    }
+
+    // deposits of token in vault, listed with LedgerEntries
+    fn ledger_list(deps: Deps, owner: &str, vault_id: u64, token: AssetInfo) -> Vec<Ledger> {
+        let msg = QueryMsg::LedgerEntries { owner: Addr::unchecked(owner), vault_id, token, start_after: None, limit: Some(MAX_LIMIT) };
+        let res: LedgerEntriesResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.entries.into_iter().map(|entry| entry.ledger).collect()
+    }
+
+    #[test]
+    fn create_vault() {
+        let mut deps = mock_dependencies();
+
+        let msg = default_instantiate();
+        let info = mock_info("tx_sender", &[]);
+
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // create 1st vault
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes.get(1).unwrap().value, "tx_sender");
+
+        let msg = QueryMsg::GetVault { vault_owner_addr: info.sender.clone(), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(0),
+                vested: Uint128::new(0),
+                claimed: Uint128::new(0),
+            }
+        );
+        assert!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).is_empty());
+
+        // create 2nd vault
+        let msg = ExecuteMsg::CreateVault { label: Some(String::from("rent")), lock_duration: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.attributes.get(2).unwrap().value, "1");
+
+        // label is unique among vaults of owner
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::VaultAlreadyExists {});
+    }
+
+    #[test]
+    fn receive() {
+        let mut deps = mock_dependencies();
+
+        let msg = default_instantiate();
+        let info = mock_info("tx_sender", &[]);
+
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // create vault
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes.get(1).unwrap().value, "tx_sender");
+
+        // receive Cw20ReceiveMsg from cw20 contract
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(120);
+        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        assert_eq!(res, Response::new()
+            .add_attribute("method", "execute_receive")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("amount", Uint128::new(100))
+            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(120).nanos()))
+            .add_attribute("vault_id", "0")
+            .add_attribute("depositor", MOCK_CONTRACT_ADDR)
+        );
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(100),
+                vested: Uint128::new(100),
+                claimed: Uint128::new(0),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(120), unlock_at: Expiration::AtTime(Timestamp::from_seconds(180)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None }]);
+    }
+
+    #[test]
+    fn withdraw() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate();
+        let info = mock_info("tx_sender", &[]);
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+
+        let _res = instantiate(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+        // create vault after 1s
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1);
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes.get(1).unwrap().value, "tx_sender");
+
+        // Deposit 100 coin after 1 min of vault creation
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(60);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        assert_eq!(res, Response::new()
+            .add_attribute("method", "execute_receive")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("amount", Uint128::new(100))
+            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(60).nanos()))
+            .add_attribute("vault_id", "0")
+            .add_attribute("depositor", MOCK_CONTRACT_ADDR)
+        );
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(100),
+                vested: Uint128::new(100),
+                claimed: Uint128::new(0),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None }]);
+
+        // withdraw in less than 1 minute fails
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(90);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(50) };
+        let info = mock_info("tx_sender", &[]);
+        let err = execute(deps.as_mut(), env, info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)) });
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(100),
+                vested: Uint128::new(100),
+                claimed: Uint128::new(0),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None }]);
+
+        // withdraw more than reserved coins fails
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(121);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(150) };
+        let info = mock_info("tx_sender", &[]);
+        let err = execute(deps.as_mut(), env, info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds { available: Uint128::new(100), requested: Uint128::new(150) });
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(100),
+                vested: Uint128::new(100),
+                claimed: Uint128::new(0),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None }]);
+
+        // withdraw 50 coins
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(121);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(50) };
+        let info = mock_info("tx_sender", &[]);
+        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        let cosmo_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("tx_sender"),
+                amount: Uint128::new(50)
+            }).unwrap(),
+            funds: vec![]
+        });
+        assert_eq!(res, Response::new()
+            .add_attribute("method", "execute_withdraw")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("get_amount", Uint128::new(50))
+            .add_attribute("remaining_amount", Uint128::new(50))
+            .add_attribute("vault_id", "0")
+            .add_message(cosmo_msg)
+        );
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(50),
+                vested: Uint128::new(50),
+                claimed: Uint128::new(50),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![Ledger { coin_amount: Uint128::new(50), claimed_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None }]);
+
+        // Deposit 100 coin more
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(150);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        assert_eq!(res, Response::new()
+            .add_attribute("method", "execute_receive")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("amount", Uint128::new(100))
+            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(150).nanos()))
+            .add_attribute("vault_id", "0")
+            .add_attribute("depositor", MOCK_CONTRACT_ADDR)
+        );
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(150),
+                vested: Uint128::new(150),
+                claimed: Uint128::new(50),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![
+            Ledger { coin_amount: Uint128::new(50), claimed_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+            Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(150), unlock_at: Expiration::AtTime(Timestamp::from_seconds(210)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+        ]);
+
+        // Try withdrawing 100 coins but fail. It can receive 50 coins but should wait for other 50 coins
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(200);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let info = mock_info("tx_sender", &[]);
+        let err = execute(deps.as_mut(), env, info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(210)) });
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(150),
+                vested: Uint128::new(150),
+                claimed: Uint128::new(50),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![
+            Ledger { coin_amount: Uint128::new(50), claimed_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+            Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(150), unlock_at: Expiration::AtTime(Timestamp::from_seconds(210)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+        ]);
+
+        // Deposit 100 coin more
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(220);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
         let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
         let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
         assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_receive")
-            .add_attribute("amount", Uint128::new(100))
-            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(60).nanos()))
+            .add_attribute("method", "execute_receive")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("amount", Uint128::new(100))
+            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(220).nanos()))
+            .add_attribute("vault_id", "0")
+            .add_attribute("depositor", MOCK_CONTRACT_ADDR)
+        );
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(250),
+                vested: Uint128::new(250),
+                claimed: Uint128::new(50),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![
+            Ledger { coin_amount: Uint128::new(50), claimed_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60), unlock_at: Expiration::AtTime(Timestamp::from_seconds(120)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+            Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(150), unlock_at: Expiration::AtTime(Timestamp::from_seconds(210)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+            Ledger { coin_amount: Uint128::new(100), claimed_amount: Uint128::new(0), receive_time: Timestamp::from_seconds(220), unlock_at: Expiration::AtTime(Timestamp::from_seconds(280)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None },
+        ]);
+
+        // Withdraw 200 coins. Total withdraw coins are 250
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(300);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(200) };
+        let info = mock_info("tx_sender", &[]);
+        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        let cosmo_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("tx_sender"),
+                amount: Uint128::new(200)
+            }).unwrap(),
+            funds: vec![]
+        });
+        assert_eq!(res, Response::new()
+            .add_attribute("method", "execute_withdraw")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("get_amount", Uint128::new(200))
+            .add_attribute("remaining_amount", Uint128::new(50))
+            .add_attribute("vault_id", "0")
+            .add_message(cosmo_msg)
+        );
+
+        // query vault
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(
+            vault,
+            VaultResponse {
+                owner_addr: String::from("tx_sender"),
+                vault_id: 0,
+                label: None,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                collected: Uint128::new(50),
+                vested: Uint128::new(50),
+                claimed: Uint128::new(250),
+            }
+        );
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))), vec![Ledger { coin_amount: Uint128::new(50), claimed_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(220), unlock_at: Expiration::AtTime(Timestamp::from_seconds(280)), vesting: None, depositor: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)), memo: None, reference: None }]);
+
+        // withdrawn deposits are removed and cursor points to the oldest remaining deposit
+        let token_key = AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).key();
+        let key = ((&Addr::unchecked("tx_sender"), 0), token_key.as_str());
+        let balance = BALANCES.load(deps.as_ref().storage, key).unwrap();
+        assert_eq!(balance.next_ledger_id, 3);
+        assert_eq!(balance.ledger_cursor, 2);
+        let ids = LEDGERS
+            .prefix(key)
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(ids, vec![2]);
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate();
+        let info = mock_info("tx_sender", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // only contract owner can update config
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            lock_duration: Some(Duration::Height(10)),
+            ..default_update_config()
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // switch to block-based lock
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            lock_duration: Some(Duration::Height(10)),
+            ..default_update_config()
+        });
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.lock_duration, Duration::Height(10));
+
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Deposit 100 coin at height 100
+        let mut env = mock_env();
+        env.block.height = 100;
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)))[0].unlock_at, Expiration::AtHeight(110));
+
+        // withdraw before 10 blocks fails
+        let mut env = mock_env();
+        env.block.height = 109;
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let err = execute(deps.as_mut(), env, info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtHeight(110) });
+
+        // withdraw after 10 blocks succeeds
+        let mut env = mock_env();
+        env.block.height = 110;
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+    }
+
+    #[test]
+    fn receive_with_lock_period() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            min_lock_duration: Some(Duration::Time(60)),
+            max_lock_duration: Some(Duration::Time(3600)),
+            ..default_instantiate()
+        };
+        let info = mock_info("tx_sender", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let receive = |lock_for: Option<Duration>, unlock_at: Option<Expiration>| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(100),
+                msg: to_json_binary(&ReceiveMsg {
+                    lock_for,
+                    unlock_at,
+                    ..receive_msg("tx_sender", 0)
+                }).unwrap(),
+            })
+        };
+        let cw20_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+
+        // lock shorter than min, longer than max, or block based fails
+        let err = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive(Some(Duration::Time(30)), None)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidLockPeriod {}));
+        let err = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive(None, Some(Expiration::AtTime(Timestamp::from_seconds(7200))))).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidLockPeriod {}));
+        let err = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive(Some(Duration::Height(100)), None)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidLockPeriod {}));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            cw20_info.clone(),
+            receive(Some(Duration::Time(600)), Some(Expiration::AtTime(Timestamp::from_seconds(600)))),
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::ConflictingLockPeriod {}));
+
+        // 1 hour tranche first, then 10 minutes tranche
+        let _res = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive(None, Some(Expiration::AtTime(Timestamp::from_seconds(3600))))).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive(Some(Duration::Time(600)), None)).unwrap();
+
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)))[0].unlock_at, Expiration::AtTime(Timestamp::from_seconds(3600)));
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)))[1].unlock_at, Expiration::AtTime(Timestamp::from_seconds(600)));
+
+        // 10 minutes tranche can be withdrawn while 1 hour tranche is locked
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1200);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(150) };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(3600)) });
+
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).len(), 1);
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)))[0].unlock_at, Expiration::AtTime(Timestamp::from_seconds(3600)));
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(3600);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+    }
+
+    #[test]
+    fn withdraw_vesting() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate();
+        let info = mock_info("tx_sender", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let receive = |vesting: VestingSchedule| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(1000),
+                msg: to_json_binary(&ReceiveMsg {
+                    vesting: Some(vesting),
+                    ..receive_msg("tx_sender", 0)
+                }).unwrap(),
+            })
+        };
+        let cw20_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+
+        // cliff after end fails
+        let vesting = VestingSchedule {
+            start_time: Timestamp::from_seconds(0),
+            cliff_time: Timestamp::from_seconds(2000),
+            end_time: Timestamp::from_seconds(1000),
+        };
+        let err = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive(vesting)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVestingSchedule {}));
+
+        // 1000 coins vesting from 0 to 1000 with cliff at 250
+        let vesting = VestingSchedule {
+            start_time: Timestamp::from_seconds(0),
+            cliff_time: Timestamp::from_seconds(250),
+            end_time: Timestamp::from_seconds(1000),
+        };
+        let _res = execute(deps.as_mut(), env, cw20_info, receive(vesting)).unwrap();
+
+        // nothing is vested before cliff
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(200);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), env, info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(1000)) });
+
+        // 400 coins are vested at 400
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(400);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(401) };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(1000)) });
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(300) };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), env, msg).unwrap();
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(vault.collected, Uint128::new(700));
+        assert_eq!(vault.vested, Uint128::new(100));
+        assert_eq!(vault.claimed, Uint128::new(300));
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)))[0].claimed_amount, Uint128::new(300));
+
+        // everything is vested after end
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1000);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(700) };
+        let _res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+        let vault = query_vault(deps.as_ref(), mock_env(), Addr::unchecked("tx_sender"), 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).unwrap();
+        assert_eq!(vault.collected, Uint128::zero());
+        assert_eq!(vault.claimed, Uint128::new(1000));
+        assert!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).is_empty());
+    }
+
+    #[test]
+    fn unbond_and_claim() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            unbonding_period: Some(Duration::Time(100)),
+            ..default_instantiate()
+        };
+        let info = mock_info("tx_sender", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Deposit 100 coin at 0
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+        // withdraw is disabled while unbonding period is set
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(60);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(30) };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::UnbondingRequired {}));
+
+        // unbonding more than withdrawable coins fails
+        let msg = ExecuteMsg::Unbond { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(150) };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds { .. }));
+
+        // unbond 30 coins at 60 and 70 coins at 100
+        let msg = ExecuteMsg::Unbond { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(30) };
+        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        assert_eq!(res.attributes.get(4).unwrap().value, "70");
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        let msg = ExecuteMsg::Unbond { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(70) };
+        let _res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+        let vault = query_vault(deps.as_ref(), mock_env(), Addr::unchecked("tx_sender"), 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).unwrap();
+        assert_eq!(vault.collected, Uint128::zero());
+
+        // nothing to claim before unbonding period
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(159);
+        let err = execute(deps.as_mut(), env, info.clone(), ExecuteMsg::Claim {}).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToClaim {}));
+
+        // 1st claim matured at 160
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(160);
+        let msg = QueryMsg::Claims { address: Addr::unchecked("tx_sender"), start_after: None, limit: None };
+        let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let claims: ClaimsResponse = from_json(&res).unwrap();
+        assert_eq!(claims, ClaimsResponse {
+            pending: vec![ClaimInfo { id: 1, claim: Claim { token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(70), release_at: Expiration::AtTime(Timestamp::from_seconds(200)) } }],
+            matured: vec![ClaimInfo { id: 0, claim: Claim { token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(30), release_at: Expiration::AtTime(Timestamp::from_seconds(160)) } }],
+        });
+        let msg = QueryMsg::Claims { address: Addr::unchecked("tx_sender"), start_after: Some(0), limit: None };
+        let claims: ClaimsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(claims.pending.len() + claims.matured.len(), 1);
+
+        let res = execute(deps.as_mut(), env, info.clone(), ExecuteMsg::Claim {}).unwrap();
+        let cosmo_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("tx_sender"),
+                amount: Uint128::new(30)
+            }).unwrap(),
+            funds: vec![]
+        });
+        assert_eq!(res, Response::new()
+            .add_attribute("method", "execute_claim")
+            .add_attribute("token", "cw20:cosmos2contract")
+            .add_attribute("amount", Uint128::new(30))
+            .add_message(cosmo_msg)
         );
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let claims = CLAIMS.prefix(&Addr::unchecked("tx_sender")).keys(deps.as_ref().storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+        assert_eq!(claims, vec![1]);
+    }
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
+    #[test]
+    fn early_withdraw() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            lock_duration: Duration::Time(100),
+            early_withdraw_penalty: Some(EarlyWithdrawPenalty {
+                rate: Decimal::percent(20),
+                linear_decay: true,
+                destination: PenaltyDestination::Redistribute {},
+            }),
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // vault_a and vault_b deposit 100 coins at 0
+        for owner in ["vault_a", "vault_b"] {
+            let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(0);
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(100),
+                msg: to_json_binary(&receive_msg(owner, 0)).unwrap(),
+            });
+            let _res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        }
+
+        // half of lock period passed, so penalty is 10% of locked coins
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(50);
+        let msg = QueryMsg::EarlyWithdrawQuote { vault_owner_addr: Addr::unchecked("vault_a"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let quote: EarlyWithdrawQuoteResponse = from_json(&res).unwrap();
+        assert_eq!(quote, EarlyWithdrawQuoteResponse {
+            amount: Uint128::new(100),
+            penalty: Uint128::new(10),
+            receive_amount: Uint128::new(90),
+        });
+
+        // more than collected coins fails
+        let msg = ExecuteMsg::EarlyWithdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(101) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("vault_a", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds { .. }));
+
+        let msg = ExecuteMsg::EarlyWithdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("vault_a", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.attributes.get(2).unwrap().value, "90");
+        assert_eq!(res.attributes.get(3).unwrap().value, "10");
+
+        // penalty is redistributed to vault_b and can be withdrawn immediately
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("vault_b"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let vault: VaultResponse = from_json(&res).unwrap();
+        assert_eq!(vault.collected, Uint128::new(110));
+        assert_eq!(vault.vested, Uint128::new(10));
+
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(10) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("vault_b", &[]), msg).unwrap();
+        assert_eq!(TOTAL_COLLECTED.load(deps.as_ref().storage, &AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).key()).unwrap(), Uint128::new(100));
+
+        // flat penalty sent to treasury
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            early_withdraw_penalty: Some(EarlyWithdrawPenalty {
+                rate: Decimal::percent(20),
+                linear_decay: false,
+                destination: PenaltyDestination::Treasury { address: Addr::unchecked("treasury") },
+            }),
+            ..default_update_config()
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::EarlyWithdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(50) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("vault_b", &[]), msg).unwrap();
+        let cosmo_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("treasury"),
+                amount: Uint128::new(10)
+            }).unwrap(),
+            funds: vec![]
+        });
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[1].msg, cosmo_msg);
+
+        // with unbonding period, withdrawn coins go to claims
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg { unbonding_period: Some(Duration::Time(100)), ..default_update_config() });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::EarlyWithdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(10) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("vault_b", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let msg = QueryMsg::Claims { address: Addr::unchecked("vault_b"), start_after: None, limit: None };
+        let claims: ClaimsResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(claims.pending, vec![ClaimInfo {
+            id: 0,
+            claim: Claim {
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                amount: Uint128::new(8),
+                release_at: Expiration::AtTime(Timestamp::from_seconds(150)),
+            },
+        }]);
+    }
+
+    #[test]
+    fn receive_errors() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), default_instantiate()).unwrap();
+        let cw20_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let deposit_msg = to_json_binary(&receive_msg("tx_sender", 0)).unwrap();
+
+        // deposit to vault not created yet
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: deposit_msg.clone(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), cw20_info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::VaultNotFound {});
+
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        // zero amount
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::zero(),
+            msg: deposit_msg,
+        });
+        let err = execute(deps.as_mut(), mock_env(), cw20_info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmount {});
+
+        // msg which is not ReceiveMsg
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(MOCK_CONTRACT_ADDR),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ExecuteMsg::Claim {}).unwrap(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), cw20_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidReceiveMsg {});
+
+        // withdraw from vault not created
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::VaultNotFound {});
+    }
+
+    #[test]
+    fn paginated_queries() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
+
+        for owner in ["vault_a", "vault_b", "vault_c"] {
+            let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+        }
+        // 35 deposits to vault_a
+        for i in 1..=35 {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(i),
+                msg: to_json_binary(&receive_msg("vault_a", 0)).unwrap(),
+            });
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        }
+
+        // default limit
+        let msg = QueryMsg::LedgerEntries { owner: Addr::unchecked("vault_a"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), start_after: None, limit: None };
+        let res: LedgerEntriesResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entries.len(), DEFAULT_LIMIT as usize);
+        assert_eq!(res.entries[0].id, 0);
+        assert_eq!(res.entries[0].ledger.coin_amount, Uint128::new(1));
+
+        // max limit
+        let msg = QueryMsg::LedgerEntries { owner: Addr::unchecked("vault_a"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), start_after: Some(4), limit: Some(100) };
+        let res: LedgerEntriesResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entries.len(), MAX_LIMIT as usize);
+        assert_eq!(res.entries[0].id, 5);
+        assert_eq!(res.entries.last().unwrap().id, 34);
+
+        let msg = QueryMsg::ListVaults { start_after: None, limit: Some(2) };
+        let res: ListVaultsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.vaults, vec![
+            VaultSummary {
+                owner_addr: String::from("vault_a"),
+                vault_id: 0,
+                label: None,
+                balances: vec![Asset { info: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(630) }],
+            },
+            VaultSummary { owner_addr: String::from("vault_b"), vault_id: 0, label: None, balances: vec![] },
+        ]);
+
+        let msg = QueryMsg::ListVaults { start_after: Some(VaultRef { owner: Addr::unchecked("vault_b"), vault_id: 0 }), limit: Some(2) };
+        let res: ListVaultsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.vaults, vec![VaultSummary { owner_addr: String::from("vault_c"), vault_id: 0, label: None, balances: vec![] }]);
+    }
+
+    #[test]
+    fn withdrawable_amount_and_simulate_withdraw() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate();
+        let info = mock_info("tx_sender", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // 100 coins unlocked at 60, 200 coins unlocked at 300,
+        // 400 coins vesting from 0 to 500 with cliff at 100
+        let deposits = [
+            (100, None, None),
+            (200, Some(Duration::Time(300)), None),
+            (400, None, Some(VestingSchedule {
+                start_time: Timestamp::from_seconds(0),
+                cliff_time: Timestamp::from_seconds(100),
+                end_time: Timestamp::from_seconds(500),
+            })),
+        ];
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        for (amount, lock_for, vesting) in deposits {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg { lock_for, vesting, ..receive_msg("tx_sender", 0) }).unwrap(),
+            });
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(120);
+
+        // 96 coins are vested at 120
+        let msg = QueryMsg::WithdrawableAmount { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), at_time: None, limit: None };
+        let res: WithdrawableAmountResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res, WithdrawableAmountResponse {
+            available: Uint128::new(196),
+            locked: Uint128::new(504),
+            unlocks: vec![
+                UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(300)), amount: Uint128::new(200) },
+                UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(500)), amount: Uint128::new(304) },
+            ],
+        });
+
+        // before cliff, 80 coins are released at cliff time
+        let msg = QueryMsg::WithdrawableAmount { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), at_time: Some(Timestamp::from_seconds(50)), limit: None };
+        let res: WithdrawableAmountResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res, WithdrawableAmountResponse {
+            available: Uint128::zero(),
+            locked: Uint128::new(700),
+            unlocks: vec![
+                UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(60)), amount: Uint128::new(100) },
+                UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(100)), amount: Uint128::new(80) },
+                UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(300)), amount: Uint128::new(200) },
+                UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(500)), amount: Uint128::new(320) },
+            ],
+        });
+
+        // unlocks of the first locked deposits are listed up to limit
+        let msg = QueryMsg::WithdrawableAmount { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), at_time: Some(Timestamp::from_seconds(50)), limit: Some(1) };
+        let res: WithdrawableAmountResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.locked, Uint128::new(700));
+        assert_eq!(res.unlocks, vec![UnlockInfo { unlock_at: Expiration::AtTime(Timestamp::from_seconds(60)), amount: Uint128::new(100) }]);
+
+        // locked deposit is skipped
+        let msg = QueryMsg::SimulateWithdraw { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(150) };
+        let res: SimulateWithdrawResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res, SimulateWithdrawResponse {
+            amount: Uint128::new(150),
+            entries: vec![
+                LedgerWithdrawal { id: 0, amount: Uint128::new(100), remaining: Uint128::zero() },
+                LedgerWithdrawal { id: 2, amount: Uint128::new(50), remaining: Uint128::new(350) },
+            ],
+        });
+
+        // simulation matches withdraw
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(150) };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::LedgerEntries { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), start_after: None, limit: None };
+        let res: LedgerEntriesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let remaining: Vec<(u64, Uint128)> = res.entries.iter().map(|e| (e.id, e.ledger.coin_amount)).collect();
+        assert_eq!(remaining, vec![(1, Uint128::new(200)), (2, Uint128::new(350))]);
+
+        // only withdrawable coins are reported
+        let msg = QueryMsg::SimulateWithdraw { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(1000) };
+        let res: SimulateWithdrawResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.amount, Uint128::new(46));
+    }
+
+    #[test]
+    fn multiple_tokens() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            tokens: vec![AssetInfo::Cw20(Addr::unchecked("token_a"))],
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let receive = |amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+
+        // token_b is not whitelisted yet
+        let err = execute(deps.as_mut(), env.clone(), mock_info("token_b", &[]), receive(200)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // only admin can add token
+        let msg = ExecuteMsg::AddToken { token: AssetInfo::Cw20(Addr::unchecked("token_b")) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let res: TokensResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::Tokens {}).unwrap()).unwrap();
+        assert_eq!(res.tokens, vec![AssetInfo::Cw20(Addr::unchecked("token_a")), AssetInfo::Cw20(Addr::unchecked("token_b"))]);
+
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("token_a", &[]), receive(100)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("token_b", &[]), receive(200)).unwrap();
+
+        // removed token can't be deposited
+        let msg = ExecuteMsg::RemoveToken { token: AssetInfo::Cw20(Addr::unchecked("token_b")) };
+        let _res = execute(deps.as_mut(), env, mock_info("admin", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("token_b", &[]), receive(200)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        let msg = QueryMsg::Balances { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: BalancesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.balances, vec![
+            TokenBalance { token: AssetInfo::Cw20(Addr::unchecked("token_a")), collected: Uint128::new(100), vested: Uint128::new(100) },
+            TokenBalance { token: AssetInfo::Cw20(Addr::unchecked("token_b")), collected: Uint128::new(200), vested: Uint128::new(200) },
+        ]);
+
+        // each token is withdrawn from its own balance
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked("token_a")), amount: Uint128::new(150) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds { available: Uint128::new(100), requested: Uint128::new(150) });
+
+        // removed token can still be withdrawn
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked("token_b")), amount: Uint128::new(150) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("token_b"),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("tx_sender"), amount: Uint128::new(150) }).unwrap(),
+            funds: vec![],
+        }));
+
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked("token_a")) };
+        let res: VaultResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.collected, Uint128::new(100));
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked("token_b")) };
+        let res: VaultResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.collected, Uint128::new(50));
+    }
+
+    #[test]
+    fn migrate_single_token() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        LEGACY_CONFIG.save(deps.as_mut().storage, &LegacyConfig {
+            owner: Addr::unchecked("admin"),
+            cw20_addr: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        }).unwrap();
+        LEGACY_VAULTS.save(deps.as_mut().storage, Addr::unchecked("tx_sender"), &LegacyVault {
+            owner_addr: Addr::unchecked("tx_sender"),
+            collected: Uint128::new(300),
+            ledger_list: vec![
+                LegacyLedger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(0) },
+                LegacyLedger { coin_amount: Uint128::new(200), receive_time: Timestamp::from_seconds(100) },
+            ],
+        }).unwrap();
+
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(cw2::get_contract_version(deps.as_ref().storage).unwrap().version, CONTRACT_VERSION);
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.owner, Some(Addr::unchecked("admin")));
+        assert_eq!(config.lock_duration, Duration::Time(60));
+        let res: TokensResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Tokens {}).unwrap()).unwrap();
+        assert_eq!(res.tokens, vec![AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))]);
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        let vault = query_vault(deps.as_ref(), env.clone(), Addr::unchecked("tx_sender"), 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).unwrap();
+        assert_eq!(vault.collected, Uint128::new(300));
+        assert_eq!(vault.vested, Uint128::new(100));
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)))[1].unlock_at, Expiration::AtTime(Timestamp::from_seconds(161)));
+
+        // migrated vault works as before
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        assert_eq!(TOTAL_COLLECTED.load(deps.as_ref().storage, &AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).key()).unwrap(), Uint128::new(200));
+        // migrated vault is vault 0 of owner
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        assert_eq!(res.attributes.get(2).unwrap().value, "1");
+
+        // migrating again doesn't change state
+        let _res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        let vault = query_vault(deps.as_ref(), env, Addr::unchecked("tx_sender"), 0, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))).unwrap();
+        assert_eq!(vault.collected, Uint128::new(200));
+    }
+
+    #[test]
+    fn native_deposit() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            tokens: vec![AssetInfo::Native(String::from("ujunox"))],
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let deposit = ExecuteMsg::Deposit(receive_msg("tx_sender", 0));
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+
+        // no funds
+        let err = execute(deps.as_mut(), env.clone(), mock_info("depositor", &[]), deposit.clone()).unwrap_err();
+        assert_eq!(err, ContractError::ZeroAmount {});
+
+        // denom not whitelisted
+        let info = mock_info("depositor", &[coin(100, "ujunox"), coin(100, "uatom")]);
+        let err = execute(deps.as_mut(), env.clone(), info, deposit.clone()).unwrap_err();
+        assert_eq!(err, ContractError::TokenNotWhitelisted { token: String::from("native:uatom") });
+
+        let info = mock_info("depositor", &[coin(100, "ujunox")]);
+        let res = execute(deps.as_mut(), env, info, deposit).unwrap();
+        assert_eq!(res.attributes.get(1).unwrap().value, "native:ujunox");
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Native(String::from("ujunox")) };
+        let vault: VaultResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(vault.collected, Uint128::new(100));
+        assert_eq!(ledger_list(deps.as_ref(), "tx_sender", 0, AssetInfo::Native(String::from("ujunox")))[0].unlock_at, Expiration::AtTime(Timestamp::from_seconds(60)));
+
+        // native coins are sent with bank msg
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Native(String::from("ujunox")), amount: Uint128::new(40) };
+        let res = execute(deps.as_mut(), env, mock_info("tx_sender", &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("tx_sender"),
+            amount: vec![coin(40, "ujunox")],
+        }));
+    }
+
+    #[test]
+    fn nft_custody() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            tokens: vec![],
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let receive_nft = |token_id: &str, lock_for: Option<Duration>, vesting: Option<VestingSchedule>| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: String::from("depositor"),
+                token_id: String::from(token_id),
+                msg: to_json_binary(&ReceiveMsg { lock_for, vesting, ..receive_msg("tx_sender", 0) }).unwrap(),
+            })
+        };
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+
+        // only whitelisted collections can deposit, and only admin adds them
+        let err = execute(deps.as_mut(), env.clone(), mock_info("collection_a", &[]), receive_nft("1", None, None)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::AddCollection { collection: String::from("collection_a") };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        for collection in ["collection_a", "collection_b"] {
+            let msg = ExecuteMsg::AddCollection { collection: String::from(collection) };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        }
+        let res: CollectionsResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Collections {}).unwrap()).unwrap();
+        assert_eq!(res.collections, vec![Addr::unchecked("collection_a"), Addr::unchecked("collection_b")]);
+
+        // NFT can't vest
+        let vesting = VestingSchedule {
+            start_time: Timestamp::from_seconds(0),
+            cliff_time: Timestamp::from_seconds(0),
+            end_time: Timestamp::from_seconds(100),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("collection_a", &[]), receive_nft("1", None, Some(vesting))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidReceiveMsg {});
+
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("collection_a", &[]), receive_nft("1", None, None)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("collection_a", &[]), receive_nft("2", Some(Duration::Time(300)), None)).unwrap();
+        let _res = execute(deps.as_mut(), env, mock_info("collection_b", &[]), receive_nft("1", None, None)).unwrap();
+
+        let msg = QueryMsg::Nfts { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, start_after: None, limit: Some(2) };
+        let res: NftsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.nfts, vec![
+            NftInfo {
+                collection: Addr::unchecked("collection_a"),
+                token_id: String::from("1"),
+                ledger: NftLedger { receive_time: Timestamp::from_seconds(0), unlock_at: Expiration::AtTime(Timestamp::from_seconds(60)) },
+            },
+            NftInfo {
+                collection: Addr::unchecked("collection_a"),
+                token_id: String::from("2"),
+                ledger: NftLedger { receive_time: Timestamp::from_seconds(0), unlock_at: Expiration::AtTime(Timestamp::from_seconds(300)) },
+            },
+        ]);
+        let start_after = NftId { collection: Addr::unchecked("collection_a"), token_id: String::from("2") };
+        let msg = QueryMsg::Nfts { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, start_after: Some(start_after), limit: Some(2) };
+        let res: NftsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.nfts.len(), 1);
+        assert_eq!(res.nfts[0].collection, Addr::unchecked("collection_b"));
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        let msg = ExecuteMsg::WithdrawNft { vault_id: 0, collection: Addr::unchecked("collection_a"), token_id: String::from("2") };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(300)) });
+
+        // only vault owner can withdraw
+        let msg = ExecuteMsg::WithdrawNft { vault_id: 0, collection: Addr::unchecked("collection_a"), token_id: String::from("1") };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NftNotFound {});
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg.clone()).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("collection_a"),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft { recipient: String::from("tx_sender"), token_id: String::from("1") }).unwrap(),
+            funds: vec![],
+        }));
+        let err = execute(deps.as_mut(), env, mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NftNotFound {});
+    }
+
+    #[test]
+    fn named_vaults() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_lock_duration: Some(Duration::Time(1000)),
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+        // lock duration of vault should be within the bounds
+        let msg = ExecuteMsg::CreateVault { label: Some(String::from("tax")), lock_duration: Some(Duration::Time(2000)) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidLockPeriod {});
+        let msg = ExecuteMsg::CreateVault { label: Some(String::from("tax")), lock_duration: Some(Duration::Time(500)) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let msg = QueryMsg::VaultsByOwner { owner: Addr::unchecked("tx_sender"), start_after: None, limit: None };
+        let res: VaultsByOwnerResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.vaults, vec![
+            Vault { owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, label: None, lock_duration: None },
             Vault {
                 owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(100),
-                ledger_list: vec![Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(60) }],
-            }
-        );
+                vault_id: 1,
+                label: Some(String::from("tax")),
+                lock_duration: Some(Duration::Time(500)),
+            },
+        ]);
+
+        let receive = |vault_id: u64, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg("tx_sender", vault_id)).unwrap(),
+        });
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(0, 100)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(1, 200)).unwrap();
+        let err = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), receive(2, 200)).unwrap_err();
+        assert_eq!(err, ContractError::VaultNotFound {});
+
+        // each vault is locked with its own lock duration
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        let msg = ExecuteMsg::Withdraw { vault_id: 1, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(200) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(500)) });
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 1, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res: VaultResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.label, Some(String::from("tax")));
+        assert_eq!(res.collected, Uint128::new(200));
+        let msg = QueryMsg::ListVaults { start_after: None, limit: None };
+        let res: ListVaultsResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let vaults: Vec<(u64, Uint128)> = res.vaults.iter().map(|v| (v.vault_id, v.balances[0].amount)).collect();
+        assert_eq!(vaults, vec![(0, Uint128::zero()), (1, Uint128::new(200))]);
+    }
+
+    #[test]
+    fn vault_transfer() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
+        for owner in ["tx_sender", "heir"] {
+            let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+        }
+
+        let receive = |owner: &str, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg(owner, 0)).unwrap(),
+        });
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("tx_sender", 100)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("tx_sender", 200)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("heir", 50)).unwrap();
+
+        let propose = |new_owner: &str, expiry: Option<Expiration>, merge: bool| ExecuteMsg::ProposeVaultTransfer {
+            vault_id: 0,
+            new_owner: String::from(new_owner),
+            expiry,
+            merge,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), propose("tx_sender", None, false)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidNewOwner {});
+
+        let expiry = Expiration::AtTime(Timestamp::from_seconds(10));
+        let res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), propose("new_owner", Some(expiry), false)).unwrap();
+        assert_eq!(res.events[0].ty, "vault_transfer_proposed");
+        // only one transfer to an address can be pending
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), propose("new_owner", None, false)).unwrap_err();
+        assert_eq!(err, ContractError::TransferAlreadyPending {});
+
+        let mut late_env = mock_env();
+        late_env.block.time = Timestamp::from_seconds(20);
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: None };
+        let err = execute(deps.as_mut(), late_env, mock_info("new_owner", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TransferExpired {});
+
+        // new proposal replaces the previous one
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), propose("heir", None, true)).unwrap();
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("new_owner", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoPendingTransfer {});
+        let msg = QueryMsg::VaultTransfer { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: VaultTransferResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.transfer, Some(VaultTransfer {
+            owner: Addr::unchecked("tx_sender"),
+            vault_id: 0,
+            new_owner: Addr::unchecked("heir"),
+            expiry: None,
+            merge: true,
+        }));
+
+        // heir merges the vault into its vault 0
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: Some(0) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("heir", &[]), msg).unwrap();
+        assert_eq!(res.events[0].ty, "vault_transfer_accepted");
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::VaultNotFound {});
+
+        // deposits of tx_sender are appended to ledger of heir
+        let msg = QueryMsg::LedgerEntries {
+            owner: Addr::unchecked("heir"),
+            vault_id: 0,
+            token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            start_after: None,
+            limit: None,
+        };
+        let res: LedgerEntriesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let entries: Vec<(u64, Uint128)> = res.entries.iter().map(|e| (e.id, e.ledger.coin_amount)).collect();
+        assert_eq!(entries, vec![(0, Uint128::new(50)), (1, Uint128::new(100)), (2, Uint128::new(200))]);
+        let msg = QueryMsg::Balances { vault_owner_addr: Addr::unchecked("heir"), vault_id: 0 };
+        let res: BalancesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.balances[0].collected, Uint128::new(350));
+        let msg = QueryMsg::Balances { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0 };
+        assert!(query(deps.as_ref(), env.clone(), msg).is_err());
+
+        let msg = QueryMsg::VaultTransfer { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: VaultTransferResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.transfer, None);
+
+        // without merge, the vault becomes a new vault of new owner
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("heir", &[]), propose("other", None, false)).unwrap();
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: Some(0) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MergeNotAllowed {});
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
+        let msg = QueryMsg::Balances { vault_owner_addr: Addr::unchecked("other"), vault_id: 1 };
+        let res: BalancesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.balances[0].collected, Uint128::new(350));
+
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), propose("new_owner", None, false)).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), ExecuteMsg::CancelVaultTransfer { vault_id: 0 }).unwrap();
+        assert_eq!(res.events[0].ty, "vault_transfer_cancelled");
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("new_owner", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoPendingTransfer {});
+
+        // vault with too many deposits can't be moved in one transaction
+        for _ in 0..=MAX_MOVE_ENTRIES {
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("other", 1)).unwrap();
+        }
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), propose("new_owner", None, false)).unwrap();
+        let msg = ExecuteMsg::AcceptVaultTransfer { into_vault_id: None };
+        let err = execute(deps.as_mut(), env, mock_info("new_owner", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManyEntries { max: 100 });
+    }
+
+    #[test]
+    fn vault_allowance() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let receive = |amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(100)).unwrap();
+        env.block.time = Timestamp::from_seconds(100);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(200)).unwrap();
+
+        let token = AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR));
+        let withdraw_from = |amount: u128| ExecuteMsg::WithdrawFrom {
+            owner: String::from("tx_sender"),
+            vault_id: 0,
+            token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            amount: Uint128::new(amount),
+            recipient: Some(String::from("payee")),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), withdraw_from(50)).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+
+        let increase = |amount: u128, expires: Option<Expiration>| ExecuteMsg::IncreaseVaultAllowance {
+            vault_id: 0,
+            spender: String::from("bot"),
+            token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            amount: Uint128::new(amount),
+            expires,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), increase(100, None)).unwrap_err();
+        assert_eq!(err, ContractError::VaultNotFound {});
+        let expires = Expiration::AtTime(Timestamp::from_seconds(50));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), increase(100, Some(expires))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExpiration {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), increase(100, None)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), increase(150, None)).unwrap();
+        let msg = ExecuteMsg::DecreaseVaultAllowance {
+            vault_id: 0,
+            spender: String::from("bot"),
+            token: token.clone(),
+            amount: Uint128::new(50),
+            expires: Some(Expiration::AtTime(Timestamp::from_seconds(1000))),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let msg = QueryMsg::Allowances { owner: Addr::unchecked("tx_sender"), vault_id: 0, start_after: None, limit: None };
+        let res: AllowancesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.allowances, vec![AllowanceInfo {
+            spender: Addr::unchecked("bot"),
+            token: token.clone(),
+            allowance: Uint128::new(200),
+            expires: Expiration::AtTime(Timestamp::from_seconds(1000)),
+        }]);
+
+        // only unlocked coins can be withdrawn
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), withdraw_from(150)).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(160)) });
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), withdraw_from(100)).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("payee"), amount: Uint128::new(100) }).unwrap(),
+            funds: vec![],
+        }));
+
+        env.block.time = Timestamp::from_seconds(200);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), withdraw_from(150)).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientAllowance { allowance: Uint128::new(100), requested: Uint128::new(150) });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), withdraw_from(100)).unwrap();
+        let msg = QueryMsg::Allowances { owner: Addr::unchecked("tx_sender"), vault_id: 0, start_after: None, limit: None };
+        let res: AllowancesResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.allowances, vec![]);
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: token.clone() };
+        let res: VaultResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.collected, Uint128::new(100));
+
+        // allowance can't be used after expiration
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), increase(100, Some(Expiration::AtTime(Timestamp::from_seconds(300))))).unwrap();
+        env.block.time = Timestamp::from_seconds(300);
+        let err = execute(deps.as_mut(), env, mock_info("bot", &[]), withdraw_from(50)).unwrap_err();
+        assert_eq!(err, ContractError::AllowanceExpired {});
+    }
+
+    #[test]
+    fn multisig_vault() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
+
+        let create = |threshold: u64| ExecuteMsg::CreateMultisigVault {
+            label: Some(String::from("treasury")),
+            lock_duration: None,
+            members: vec![
+                Member { addr: Addr::unchecked("alice"), weight: 2 },
+                Member { addr: Addr::unchecked("bob"), weight: 1 },
+                Member { addr: Addr::unchecked("carol"), weight: 1 },
+            ],
+            threshold,
+            max_voting_period: Duration::Time(1000),
+        };
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        env.block.height = 100;
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), create(5)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidThreshold {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), create(3)).unwrap();
+
+        // multisig vault is owned by the contract
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg(MOCK_CONTRACT_ADDR, 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+        env.block.time = Timestamp::from_seconds(100);
+        env.block.height = 101;
+        let withdraw = MultisigAction::Withdraw {
+            token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            amount: Uint128::new(100),
+            recipient: Addr::unchecked("payee"),
+        };
+        let propose = |action: MultisigAction, latest: Option<Expiration>| ExecuteMsg::Propose {
+            vault_id: 0,
+            title: String::from("title"),
+            description: String::from("description"),
+            action,
+            latest,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("dave", &[]), propose(withdraw.clone(), None)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let latest = Some(Expiration::AtTime(Timestamp::from_seconds(2000)));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(withdraw.clone(), latest)).unwrap_err();
+        assert_eq!(err, ContractError::WrongExpiration {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(withdraw, None)).unwrap();
+
+        let vote = |proposal_id: u64, vote: Vote| ExecuteMsg::Vote { proposal_id, vote };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), vote(1, Vote::Yes)).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyVoted {});
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Execute { proposal_id: 1 }).unwrap_err();
+        assert_eq!(err, ContractError::WrongExecuteStatus {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), vote(1, Vote::Yes)).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), vote(1, Vote::No)).unwrap_err();
+        assert_eq!(err, ContractError::NotOpen {});
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Execute { proposal_id: 1 }).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("payee"), amount: Uint128::new(100) }).unwrap(),
+            funds: vec![],
+        }));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Execute { proposal_id: 1 }).unwrap_err();
+        assert_eq!(err, ContractError::WrongExecuteStatus {});
+
+        // members can only be changed by proposal
+        let threshold = MultisigAction::UpdateThreshold { threshold: 4 };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(threshold, None)).unwrap();
+        let members = MultisigAction::UpdateMembers {
+            add: vec![Member { addr: Addr::unchecked("dave"), weight: 1 }],
+            remove: vec![Addr::unchecked("carol")],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(members, None)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), vote(3, Vote::Yes)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Execute { proposal_id: 3 }).unwrap();
+
+        let msg = QueryMsg::Voters { vault_id: 0, start_after: None, limit: None };
+        let res: VoterListResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.voters, vec![
+            VoterDetail { addr: String::from("alice"), weight: 2 },
+            VoterDetail { addr: String::from("bob"), weight: 1 },
+            VoterDetail { addr: String::from("dave"), weight: 1 },
+        ]);
+
+        // voting weight is taken from members when proposal was created
+        env.block.height = 102;
+        let err = execute(deps.as_mut(), env.clone(), mock_info("dave", &[]), vote(2, Vote::Yes)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), vote(2, Vote::No)).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Close { proposal_id: 2 }).unwrap_err();
+        assert_eq!(err, ContractError::NotExpired {});
+
+        env.block.time = Timestamp::from_seconds(1100);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), vote(2, Vote::Yes)).unwrap_err();
+        assert_eq!(err, ContractError::ProposalExpired {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Close { proposal_id: 2 }).unwrap();
+
+        let msg = QueryMsg::Proposal { proposal_id: 2 };
+        let res: ProposalResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.status, Status::Rejected);
+        assert_eq!(res.threshold, ThresholdResponse::AbsoluteCount { weight: 3, total_weight: 4 });
+        assert_eq!(res.expires, Expiration::AtTime(Timestamp::from_seconds(1100)));
+
+        let msg = QueryMsg::ListProposals { start_after: Some(1), limit: None };
+        let res: ProposalListResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let statuses: Vec<Status> = res.proposals.into_iter().map(|proposal| proposal.status).collect();
+        assert_eq!(statuses, vec![Status::Rejected, Status::Executed]);
+
+        // with unbonding period, recipient claims withdrawn coins later
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg { unbonding_period: Some(Duration::Time(100)), ..default_update_config() });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(50),
+            msg: to_json_binary(&receive_msg(MOCK_CONTRACT_ADDR, 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        env.block.time = Timestamp::from_seconds(1200);
+        let withdraw = MultisigAction::Withdraw {
+            token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            amount: Uint128::new(50),
+            recipient: Addr::unchecked("payee"),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(withdraw, None)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), vote(4, Vote::Yes)).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), ExecuteMsg::Execute { proposal_id: 4 }).unwrap();
+        assert!(res.messages.is_empty());
+        env.block.time = Timestamp::from_seconds(1300);
+        let res = execute(deps.as_mut(), env, mock_info("payee", &[]), ExecuteMsg::Claim {}).unwrap();
+        assert_eq!(res.messages[0].msg, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).transfer_msg(&Addr::unchecked("payee"), Uint128::new(50)).unwrap());
+    }
+
+    #[test]
+    fn inheritance() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let receive = |amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(100)).unwrap();
+
+        let set_beneficiaries = |shares: [(&str, u64); 2]| ExecuteMsg::SetBeneficiaries {
+            vault_id: 0,
+            beneficiaries: shares
+                .iter()
+                .map(|(addr, percent)| Beneficiary { addr: Addr::unchecked(*addr), share: Decimal::percent(*percent) })
+                .collect(),
+            inactivity_period: Duration::Time(500),
+        };
+        env.block.time = Timestamp::from_seconds(100);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), set_beneficiaries([("alice", 50), ("bob", 40)])).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBeneficiaries {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), set_beneficiaries([("alice", 75), ("bob", 25)])).unwrap();
+
+        let claim = ExecuteMsg::ClaimInheritance {
+            owner: String::from("tx_sender"),
+            vault_id: 0,
+            token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+        };
+        env.block.time = Timestamp::from_seconds(400);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim.clone()).unwrap_err();
+        assert_eq!(err, ContractError::OwnerActive { claimable_at: Expiration::AtTime(Timestamp::from_seconds(600)) });
+
+        // heartbeat resets inactivity period
+        env.block.time = Timestamp::from_seconds(450);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::Heartbeat {}).unwrap();
+        env.block.time = Timestamp::from_seconds(900);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim.clone()).unwrap_err();
+        assert_eq!(err, ContractError::OwnerActive { claimable_at: Expiration::AtTime(Timestamp::from_seconds(950)) });
+
+        // deposit doesn't reset it
+        env.block.time = Timestamp::from_seconds(1000);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(200)).unwrap();
+
+        // beneficiaries can only take unlocked coins
+        env.block.time = Timestamp::from_seconds(1010);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), claim.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim.clone()).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from(MOCK_CONTRACT_ADDR),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("alice"), amount: Uint128::new(100) }).unwrap(),
+            funds: vec![],
+        }));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), claim.clone()).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(1060)) });
+
+        env.block.time = Timestamp::from_seconds(1100);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), claim.clone()).unwrap();
+        assert_eq!(res.attributes[5], attr("amount", "75"));
+        let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim.clone()).unwrap();
+        assert_eq!(res.attributes[5], attr("amount", "125"));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), claim).unwrap_err();
+        assert_eq!(err, ContractError::NothingToInherit {});
 
-        // withdraw in less than 1 minute fails
+        let msg = QueryMsg::Inheritance { owner: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: InheritanceResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(res.claimable);
+        assert_eq!(res.last_activity, Some(Activity { height: env.block.height, time: Timestamp::from_seconds(450) }));
+        assert_eq!(res.claimable_at, Some(Expiration::AtTime(Timestamp::from_seconds(950))));
+        let claimed: Vec<Uint128> = res.beneficiaries.iter().map(|b| b.claimed[0].amount).collect();
+        assert_eq!(claimed, vec![Uint128::new(225), Uint128::new(75)]);
+    }
+
+    #[test]
+    fn guardian_recovery() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
         let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(90);
-        let msg = ExecuteMsg::Withdraw { amount: Uint128::new(50) };
-        let info = mock_info("tx_sender", &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.get(1).unwrap().value, "false");
+        env.block.time = Timestamp::from_seconds(0);
+        let msg = ExecuteMsg::CreateVault { label: Some(String::from("rent")), lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let set_guardians = |threshold: u64| ExecuteMsg::SetGuardians {
+            vault_id: 0,
+            guardians: vec![String::from("guardian1"), String::from("guardian2"), String::from("guardian3")],
+            threshold,
+            delay: Duration::Time(100),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), set_guardians(4)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidGuardianThreshold {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), set_guardians(2)).unwrap();
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(100),
-                ledger_list: vec![Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(60) }],
-            }
-        );
+        let initiate = ExecuteMsg::InitiateRecovery {
+            owner: String::from("tx_sender"),
+            vault_id: 0,
+            new_owner: String::from("new_key"),
+        };
+        let approve = ExecuteMsg::ApproveRecovery { owner: String::from("tx_sender"), vault_id: 0 };
+        let recover = ExecuteMsg::ExecuteRecovery { owner: String::from("tx_sender"), vault_id: 0 };
+        env.block.time = Timestamp::from_seconds(10);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("stranger", &[]), initiate.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("guardian1", &[]), initiate.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("guardian1", &[]), approve.clone()).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyApproved {});
+        let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), recover.clone()).unwrap_err();
+        assert_eq!(err, ContractError::RecoveryNotReady {});
 
-        // withdraw more than reserved coins fails
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(121);
-        let msg = ExecuteMsg::Withdraw { amount: Uint128::new(150) };
-        let info = mock_info("tx_sender", &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.get(1).unwrap().value, "false");
+        // owner still holding the key can cancel
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::CancelRecovery { vault_id: 0 }).unwrap();
+        let msg = QueryMsg::Recovery { owner: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: RecoveryResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.recovery, None);
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        env.block.time = Timestamp::from_seconds(20);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("guardian1", &[]), initiate.clone()).unwrap();
+        env.block.time = Timestamp::from_seconds(30);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("guardian2", &[]), approve).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("guardian3", &[]), initiate).unwrap_err();
+        assert_eq!(err, ContractError::RecoveryAlreadyPending {});
+        let msg = QueryMsg::Recovery { owner: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: RecoveryResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.recovery.unwrap().ready_at, Some(Expiration::AtTime(Timestamp::from_seconds(130))));
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(100),
-                ledger_list: vec![Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(60) }],
-            }
-        );
+        env.block.time = Timestamp::from_seconds(100);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), recover.clone()).unwrap_err();
+        assert_eq!(err, ContractError::RecoveryNotReady {});
+        env.block.time = Timestamp::from_seconds(130);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::CancelRecovery { vault_id: 0 }).unwrap_err();
+        assert_eq!(err, ContractError::RecoveryReady {});
+        // label taken by new owner doesn't block recovery
+        let msg = ExecuteMsg::CreateVault { label: Some(String::from("rent")), lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("new_key", &[]), msg).unwrap();
+        // old owner has settled rewards and a pending claim, and more rewards accrue in the vault
+        let fund = |amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("funder"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&ReceiveHookMsg::FundRewards { staked_token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) }).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), fund(100)).unwrap();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg { unbonding_period: Some(Duration::Time(100)), ..default_update_config() });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Unbond { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(40) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), fund(60)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), recover).unwrap();
 
-        // withdraw 50 coins
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(121);
-        let msg = ExecuteMsg::Withdraw { amount: Uint128::new(50) };
-        let info = mock_info("tx_sender", &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        let cosmo_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: String::from(MOCK_CONTRACT_ADDR),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: String::from("tx_sender"),
-                amount: Uint128::new(50)
-            }).unwrap(),
-            funds: vec![]
+        // deposits and guardians move to new owner
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("new_key"), vault_id: 1, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res: VaultResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.collected, Uint128::new(60));
+        assert_eq!(res.label, None);
+        // rewards and claims go to new owner too
+        let msg = QueryMsg::PendingRewards { owner: Addr::unchecked("new_key"), vault_id: 1 };
+        let res: PendingRewardsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.rewards, vec![Asset { info: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(160) }]);
+        let msg = QueryMsg::Claims { address: Addr::unchecked("new_key"), start_after: None, limit: None };
+        let res: ClaimsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.pending, vec![ClaimInfo { id: 1, claim: Claim { token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(40), release_at: Expiration::AtTime(Timestamp::from_seconds(230)) } }]);
+        let msg = QueryMsg::Claims { address: Addr::unchecked("tx_sender"), start_after: None, limit: None };
+        let res: ClaimsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(res.pending.is_empty() && res.matured.is_empty());
+        let msg = QueryMsg::PendingRewards { owner: Addr::unchecked("tx_sender"), vault_id: 0 };
+        let res: PendingRewardsResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.rewards, vec![]);
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        query(deps.as_ref(), env.clone(), msg).unwrap_err();
+        let msg = QueryMsg::Recovery { owner: Addr::unchecked("new_key"), vault_id: 1 };
+        let res: RecoveryResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.guardians.unwrap().threshold, 2);
+        assert_eq!(res.recovery, None);
+    }
+
+    #[test]
+    fn admin_transfer() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            unbonding_period: Some(Duration::Time(100)),
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // optional settings can be removed
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            unset: vec![ConfigField::UnbondingPeriod],
+            ..default_update_config()
+        });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::AddCollection { collection: String::from("collection") };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::SetPauser { pauser: Some(String::from("pauser")) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let res: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(res, ConfigResponse {
+            owner: Some(Addr::unchecked("admin")),
+            pending_admin: None,
+            lock_duration: Duration::Time(60),
+            min_lock_duration: None,
+            max_lock_duration: None,
+            unbonding_period: None,
+            early_withdraw_penalty: None,
+            wind_down_delay: None,
+            max_pause_duration: None,
+            tokens: vec![AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR))],
+            collections: vec![Addr::unchecked("collection")],
+            pauser: Some(Addr::unchecked("pauser")),
         });
-        assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_withdraw")
-            .add_attribute("is_success", "true")
-            .add_attribute("get_amount", Uint128::new(50))
-            .add_attribute("remaining_amount", Uint128::new(50))
-            .add_message(cosmo_msg)
-        );
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let propose = ExecuteMsg::ProposeNewAdmin { new_admin: String::from("new_admin") };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), propose.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+        assert_eq!(err, ContractError::NoPendingAdmin {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), propose).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), ExecuteMsg::AcceptAdmin {}).unwrap();
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(50),
-                ledger_list: vec![Ledger { coin_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60) }],
-            }
-        );
+        let add_token = ExecuteMsg::AddToken { token: AssetInfo::Native(String::from("ujunox")) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), add_token.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), add_token.clone()).unwrap();
+        let res: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(res.owner, Some(Addr::unchecked("new_admin")));
+        assert_eq!(res.pending_admin, None);
 
-        // Deposit 100 coin more
+        // nobody can change config after admin is renounced
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), ExecuteMsg::RenounceAdmin {}).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), add_token).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(res.owner, None);
+    }
+
+    #[test]
+    fn pause() {
+        let mut deps = mock_dependencies();
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), default_instantiate()).unwrap();
         let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(150);
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from(MOCK_CONTRACT_ADDR),
+        env.block.time = Timestamp::from_seconds(0);
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        let receive = || ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
             amount: Uint128::new(100),
-            msg: to_binary(&ReceiveMsg { vault_owner_addr: Addr::unchecked("tx_sender") }).unwrap(),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
         });
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_receive")
-            .add_attribute("amount", Uint128::new(100))
-            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(150).nanos()))
-        );
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive()).unwrap();
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let set_pauser = ExecuteMsg::SetPauser { pauser: Some(String::from("pauser")) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), set_pauser.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), set_pauser).unwrap();
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(150),
-                ledger_list: vec![
-                    Ledger { coin_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60) },
-                    Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(150) },
-                ],
-            }
-        );
+        let msg = ExecuteMsg::Pause { deposits: false, withdrawals: false, vault_creation: false, until: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPause {});
+        let res: PauseStatusResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert!(!res.deposits && !res.withdrawals && !res.vault_creation);
+        assert!(PAUSE.may_load(deps.as_ref().storage).unwrap().is_none());
 
-        // Try withdrawing 100 coins but fail. It can receive 50 coins but should wait for other 50 coins
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(200);
-        let msg = ExecuteMsg::Withdraw { amount: Uint128::new(100) };
-        let info = mock_info("tx_sender", &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.get(1).unwrap().value, "false");
+        // deposits and withdrawals are paused independently
+        env.block.time = Timestamp::from_seconds(100);
+        let msg = ExecuteMsg::Pause { deposits: true, withdrawals: false, vault_creation: false, until: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive()).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let withdraw = || ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(50) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), withdraw()).unwrap();
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let msg = ExecuteMsg::Pause { deposits: false, withdrawals: true, vault_creation: false, until: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), withdraw()).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let res: PauseStatusResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert_eq!(res, PauseStatusResponse {
+            deposits: true,
+            withdrawals: true,
+            vault_creation: false,
+            until: None,
+            pauser: Some(Addr::unchecked("pauser")),
+        });
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(150),
-                ledger_list: vec![
-                    Ledger { coin_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60) },
-                    Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(150) },
-                ],
-            }
-        );
+        // only admin can unpause
+        let err = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), ExecuteMsg::Unpause {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), ExecuteMsg::Unpause {}).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive()).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), withdraw()).unwrap();
+
+        // pauser can extend pause, but not shorten it
+        let pause = |until: u64| ExecuteMsg::Pause {
+            deposits: true,
+            withdrawals: false,
+            vault_creation: false,
+            until: Some(Expiration::AtTime(Timestamp::from_seconds(until))),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), pause(200)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), pause(250)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("pauser", &[]), pause(101)).unwrap();
+        let res: PauseStatusResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert_eq!(res.until, Some(Expiration::AtTime(Timestamp::from_seconds(250))));
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), pause(101)).unwrap();
+        let res: PauseStatusResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert_eq!(res.until, Some(Expiration::AtTime(Timestamp::from_seconds(101))));
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), ExecuteMsg::Unpause {}).unwrap();
+
+        // pause ends at until
+        let until = Some(Expiration::AtTime(Timestamp::from_seconds(300)));
+        let msg = ExecuteMsg::Pause { deposits: false, withdrawals: false, vault_creation: true, until };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: Some(String::from("rent")), lock_duration: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        env.block.time = Timestamp::from_seconds(300);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        let res: PauseStatusResponse = from_json(query(deps.as_ref(), env, QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert!(!res.deposits && !res.withdrawals && !res.vault_creation);
+    }
+
+    #[test]
+    fn wind_down() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            lock_duration: Duration::Time(1000),
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            wind_down_delay: Some(Duration::Time(100)),
+            max_pause_duration: Some(Duration::Time(500)),
+            ..default_update_config()
+        });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
 
-        // Deposit 100 coin more
         let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(220);
+        env.block.time = Timestamp::from_seconds(0);
+        let receive = |owner: &str, vault_id: u64, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg(owner, vault_id)).unwrap(),
+        });
+        for (owner, label) in [("tx_sender", None), ("tx_sender", Some(String::from("rent"))), ("other", None)] {
+            let msg = ExecuteMsg::CreateVault { label, lock_duration: None };
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(owner, &[]), msg).unwrap();
+        }
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("tx_sender", 0, 100)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("tx_sender", 1, 50)).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("other", 0, 100)).unwrap();
+        // locked NFT, locked pool shares and a claim of other
+        let msg = ExecuteMsg::AddCollection { collection: String::from("collection") };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from("other"),
+            token_id: String::from("1"),
+            msg: to_json_binary(&receive_msg("other", 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("collection", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreatePooledVault { label: None, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from(MOCK_CONTRACT_ADDR),
+            sender: String::from("other"),
             amount: Uint128::new(100),
-            msg: to_binary(&ReceiveMsg { vault_owner_addr: Addr::unchecked("tx_sender") }).unwrap(),
+            msg: to_json_binary(&receive_msg(MOCK_CONTRACT_ADDR, 0)).unwrap(),
         });
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_receive")
-            .add_attribute("amount", Uint128::new(100))
-            .add_attribute("timestamp", Uint64::new(Timestamp::from_seconds(220).nanos()))
-        );
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(10),
+            msg: to_json_binary(&ReceiveMsg { lock_for: Some(Duration::Time(0)), ..receive_msg("other", 0) }).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Unbond { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(10) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
+        // multisig treasury, inheritance of unlocked coins and rewards
+        let msg = ExecuteMsg::CreateMultisigVault {
+            label: None,
+            lock_duration: None,
+            members: vec![Member { addr: Addr::unchecked("alice"), weight: 1 }],
+            threshold: 1,
+            max_voting_period: Duration::Time(1000),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(MOCK_CONTRACT_ADDR, 1, 100)).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("late", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("depositor"),
+            amount: Uint128::new(20),
+            msg: to_json_binary(&ReceiveMsg { lock_for: Some(Duration::Time(0)), ..receive_msg("late", 0) }).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        let msg = ExecuteMsg::SetBeneficiaries {
+            vault_id: 0,
+            beneficiaries: vec![Beneficiary { addr: Addr::unchecked("heir"), share: Decimal::percent(100) }],
+            inactivity_period: Duration::Time(100),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("late", &[]), msg).unwrap();
+        // 150 of tx_sender, 100 of other and 20 of late earn 1 per coin
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("funder"),
+            amount: Uint128::new(270),
+            msg: to_json_binary(&ReceiveHookMsg::FundRewards { staked_token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) }).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        // only a long pause of withdrawals lets users start wind-down
+        for (deposits, vault_creation) in [(true, false), (false, true)] {
+            env.block.time = Timestamp::from_seconds(10);
+            let msg = ExecuteMsg::Pause { deposits, withdrawals: false, vault_creation, until: None };
+            let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+            env.block.time = Timestamp::from_seconds(600);
+            let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::StartWindDown {}).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), ExecuteMsg::Unpause {}).unwrap();
+        }
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
-                owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(250),
-                ledger_list: vec![
-                    Ledger { coin_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(60) },
-                    Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(150) },
-                    Ledger { coin_amount: Uint128::new(100), receive_time: Timestamp::from_seconds(220) },
-                ],
-            }
-        );
+        env.block.time = Timestamp::from_seconds(10);
+        let msg = ExecuteMsg::Pause { deposits: false, withdrawals: true, vault_creation: false, until: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        env.block.time = Timestamp::from_seconds(100);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::StartWindDown {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::EmergencyExit {}).unwrap_err();
+        assert_eq!(err, ContractError::NotWindingDown {});
+        let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), ExecuteMsg::Claim {}).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
 
-        // Withdraw 200 coins. Total withdraw coins are 250
-        let mut env = mock_env();
-        env.block.time = Timestamp::from_seconds(300);
-        let msg = ExecuteMsg::Withdraw { amount: Uint128::new(200) };
-        let info = mock_info("tx_sender", &[]);
-        let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
-        let cosmo_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        // users can start wind-down when contract is paused longer than max pause duration
+        env.block.time = Timestamp::from_seconds(600);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::StartWindDown {}).unwrap();
+        let res: WindDownStatusResponse = from_json(query(deps.as_ref(), env.clone(), QueryMsg::WindDownStatus {}).unwrap()).unwrap();
+        assert_eq!(res, WindDownStatusResponse {
+            wind_down: Some(WindDown {
+                starts_at: Expiration::AtTime(Timestamp::from_seconds(700)),
+                triggered_by: Addr::unchecked("tx_sender"),
+            }),
+            active: false,
+        });
+
+        env.block.time = Timestamp::from_seconds(700);
+        let err = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("other", 0, 100)).unwrap_err();
+        assert_eq!(err, ContractError::WindingDown {});
+        let err = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), ExecuteMsg::CancelWindDown {}).unwrap_err();
+        assert_eq!(err, ContractError::WindingDown {});
+
+        // emergency exit takes all vaults of sender regardless of locks and pause
+        let res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::EmergencyExit {}).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: String::from(MOCK_CONTRACT_ADDR),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: String::from("tx_sender"),
-                amount: Uint128::new(200)
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: String::from("tx_sender"), amount: Uint128::new(150) }).unwrap(),
+            funds: vec![],
+        }));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), ExecuteMsg::EmergencyExit {}).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // claims and pool shares come out regardless of pause and locks
+        let res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), ExecuteMsg::Claim {}).unwrap();
+        assert_eq!(res.messages[0].msg, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).transfer_msg(&Addr::unchecked("other"), Uint128::new(10)).unwrap());
+        let msg = ExecuteMsg::Redeem { vault_id: 0, shares: Uint128::new(100_000_000) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).transfer_msg(&Addr::unchecked("other"), Uint128::new(100)).unwrap());
+        let res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), ExecuteMsg::ClaimRewards { vault_id: 0 }).unwrap();
+        assert_eq!(res.messages[0].msg, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).transfer_msg(&Addr::unchecked("other"), Uint128::new(100)).unwrap());
+        let msg = ExecuteMsg::ClaimInheritance { owner: String::from("late"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("heir", &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).transfer_msg(&Addr::unchecked("heir"), Uint128::new(20)).unwrap());
+
+        // multisig withdraws locked coins at once, even with unbonding period
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg { unbonding_period: Some(Duration::Time(100)), ..default_update_config() });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Propose {
+            vault_id: 1,
+            title: String::from("title"),
+            description: String::from("description"),
+            action: MultisigAction::Withdraw {
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                amount: Uint128::new(100),
+                recipient: Addr::unchecked("alice"),
+            },
+            latest: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ExecuteMsg::Execute { proposal_id: 1 }).unwrap();
+        assert_eq!(res.messages[0].msg, AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)).transfer_msg(&Addr::unchecked("alice"), Uint128::new(100)).unwrap());
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg { unset: vec![ConfigField::UnbondingPeriod], ..default_update_config() });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+        // withdraw ignores locks
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), ExecuteMsg::Unpause {}).unwrap();
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
+        let msg = ExecuteMsg::WithdrawNft { vault_id: 0, collection: Addr::unchecked("collection"), token_id: String::from("1") };
+        let _res = execute(deps.as_mut(), env, mock_info("other", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn deposit_policy() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            tokens: vec![AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), AssetInfo::Native(String::from("ujunox"))],
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        let receive = |sender: &str| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(sender),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&receive_msg("tx_sender", 0)).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("depositor")).unwrap();
+
+        // policy is checked against cw20 sender, and vault owner can always deposit
+        let msg = ExecuteMsg::SetDepositPolicy { vault_id: 0, policy: DepositPolicy::Allowlist };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("depositor")).unwrap_err();
+        assert_eq!(err, ContractError::DepositNotAllowed {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("tx_sender")).unwrap();
+        let msg = ExecuteMsg::UpdateDepositors { vault_id: 0, add: vec![String::from("depositor")], remove: vec![] };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("depositor")).unwrap();
+
+        // the same list becomes denylist
+        let msg = ExecuteMsg::SetDepositPolicy { vault_id: 0, policy: DepositPolicy::Denylist };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("depositor")).unwrap_err();
+        assert_eq!(err, ContractError::DepositNotAllowed {});
+        let deposit = ExecuteMsg::Deposit(receive_msg("tx_sender", 0));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("depositor", &[coin(100, "ujunox")]), deposit.clone()).unwrap_err();
+        assert_eq!(err, ContractError::DepositNotAllowed {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("stranger", &[coin(100, "ujunox")]), deposit).unwrap();
+
+        let msg = QueryMsg::DepositPolicy { owner: Addr::unchecked("tx_sender"), vault_id: 0, start_after: None, limit: None };
+        let res: DepositPolicyResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, DepositPolicyResponse {
+            policy: DepositPolicy::Denylist,
+            depositors: vec![Addr::unchecked("depositor")],
+        });
+        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)) };
+        let res: VaultResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.collected, Uint128::new(300));
+    }
+
+    #[test]
+    fn deposit_reference() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            tokens: vec![AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), AssetInfo::Native(String::from("ujunox"))],
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        for owner in ["tx_sender", "merchant"] {
+            let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+        }
+
+        let receive = |reference: &str| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("payer"),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg {
+                memo: Some(String::from("invoice 42")),
+                reference: Some(String::from(reference)),
+                ..receive_msg("tx_sender", 0)
             }).unwrap(),
-            funds: vec![]
         });
-        assert_eq!(res, Response::new()
-            .add_attribute("method", "execute_withdraw")
-            .add_attribute("is_success", "true")
-            .add_attribute("get_amount", Uint128::new(200))
-            .add_attribute("remaining_amount", Uint128::new(50))
-            .add_message(cosmo_msg)
-        );
+        let res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("inv-42")).unwrap();
+        assert_eq!(res.attributes[5..], [attr("depositor", "payer"), attr("reference", "inv-42")]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("inv-42")).unwrap_err();
+        assert_eq!(err, ContractError::ReferenceAlreadyUsed { reference: String::from("inv-42") });
+        let err = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMemo {});
 
-        // query vault
-        let msg = QueryMsg::GetVault { vault_owner_addr: Addr::unchecked("tx_sender") };
-        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let deposit = ExecuteMsg::Deposit(ReceiveMsg {
+            reference: Some(String::from("inv-43")),
+            ..receive_msg("merchant", 0)
+        });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("payer", &[coin(50, "ujunox")]), deposit).unwrap();
 
-        let vault: Vault = from_binary(&res).unwrap();
-        assert_eq!(
-            vault,
-            Vault {
+        // ledger entry records depositor, memo and reference
+        let msg = QueryMsg::LedgerEntries { owner: Addr::unchecked("tx_sender"), vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), start_after: None, limit: None };
+        let res: LedgerEntriesResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entries[0].ledger.depositor, Some(Addr::unchecked("payer")));
+        assert_eq!(res.entries[0].ledger.memo, Some(String::from("invoice 42")));
+        assert_eq!(res.entries[0].ledger.reference, Some(String::from("inv-42")));
+
+        // deposit can be looked up after its coins are withdrawn
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env, mock_info("tx_sender", &[]), msg).unwrap();
+        let msg = QueryMsg::DepositByReference { reference: String::from("inv-42") };
+        let res: DepositInfo = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, DepositInfo {
+            id: 0,
+            deposit: Deposit {
+                depositor: Addr::unchecked("payer"),
                 owner_addr: Addr::unchecked("tx_sender"),
-                collected: Uint128::new(50),
-                ledger_list: vec![Ledger { coin_amount: Uint128::new(50), receive_time: Timestamp::from_seconds(220) }],
-            }
-        );
+                vault_id: 0,
+                token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+                amount: Uint128::new(100),
+                memo: Some(String::from("invoice 42")),
+                reference: Some(String::from("inv-42")),
+                receive_time: mock_env().block.time,
+            },
+        });
+
+        let msg = QueryMsg::DepositsByDepositor { depositor: Addr::unchecked("payer"), start_after: Some(0), limit: None };
+        let res: DepositsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.deposits.len(), 1);
+        assert_eq!(res.deposits[0].id, 1);
+        assert_eq!(res.deposits[0].deposit.owner_addr, Addr::unchecked("merchant"));
+        assert_eq!(res.deposits[0].deposit.token, AssetInfo::Native(String::from("ujunox")));
+    }
+
+    #[test]
+    fn pooled_vault() {
+        let mut deps = mock_dependencies();
+        let token = AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR));
+        let msg = InstantiateMsg {
+            tokens: vec![token.clone()],
+            early_withdraw_penalty: Some(EarlyWithdrawPenalty {
+                rate: Decimal::percent(50),
+                linear_decay: false,
+                destination: PenaltyDestination::Redistribute {},
+            }),
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreatePooledVault { label: None, token: token.clone(), lock_duration: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(res.attributes[2], attr("vault_id", "0"));
+
+        let receive = |sender: &str, owner: &str, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(sender),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg(owner, 0)).unwrap(),
+        });
+        // the first deposit mints shares with virtual offset, and later deposits at the share price
+        let res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("alice", MOCK_CONTRACT_ADDR, 100)).unwrap();
+        assert_eq!(res.attributes.last().unwrap(), attr("shares", "100000000"));
+        let res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("bob", MOCK_CONTRACT_ADDR, 50)).unwrap();
+        assert_eq!(res.attributes.last().unwrap(), attr("shares", "50000000"));
+
+        // shares are locked like deposits
+        let msg = ExecuteMsg::Redeem { vault_id: 0, shares: Uint128::new(100_000_000) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(mock_env().block.time.plus_seconds(60)) });
+
+        // penalty redistributed to the pool raises the share price
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("carol", "carol", 150)).unwrap();
+        let msg = ExecuteMsg::EarlyWithdraw { vault_id: 0, token: token.clone(), amount: Uint128::new(150) };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), msg).unwrap();
+
+        let msg = QueryMsg::TotalAssets { vault_id: 0 };
+        let res: TotalAssetsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, TotalAssetsResponse { token: token.clone(), total_assets: Uint128::new(225), total_shares: Uint128::new(150_000_000) });
+        let msg = QueryMsg::PreviewRedeem { vault_id: 0, shares: Uint128::new(100_000_000) };
+        let res: AssetsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.assets, Uint128::new(149));
+        let msg = QueryMsg::ConvertToShares { vault_id: 0, assets: Uint128::new(149) };
+        let res: SharesResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.shares, Uint128::new(99_553_097));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::Redeem { vault_id: 0, shares: Uint128::new(100_000_000) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, token.transfer_msg(&Addr::unchecked("alice"), Uint128::new(149)).unwrap());
+        let msg = QueryMsg::PoolShare { vault_id: 0, owner: Addr::unchecked("bob") };
+        let res: PoolShareResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.shares, Uint128::new(50_000_000));
+        assert_eq!(res.assets, Uint128::new(75));
+
+        // NFT can't be sent to pooled vault, and pool takes only its token
+        let msg = ExecuteMsg::AddCollection { collection: String::from("collection") };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: String::from("alice"),
+            token_id: String::from("1"),
+            msg: to_json_binary(&receive_msg(MOCK_CONTRACT_ADDR, 0)).unwrap(),
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("collection", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidReceiveMsg {});
+    }
+
+    #[test]
+    fn rewards() {
+        let mut deps = mock_dependencies();
+        let token = AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR));
+        let reward_token = AssetInfo::Cw20(Addr::unchecked("reward_token"));
+        let msg = InstantiateMsg {
+            tokens: vec![token.clone(), reward_token.clone(), AssetInfo::Native(String::from("ujunox"))],
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreatePooledVault { label: None, token: token.clone(), lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), msg).unwrap();
+
+        let receive = |owner: &str, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from(owner),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&receive_msg(owner, 0)).unwrap(),
+        });
+        let fund = |amount: u128, staked_token: &AssetInfo| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("funder"),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&ReceiveHookMsg::FundRewards { staked_token: staked_token.clone() }).unwrap(),
+        });
+        for (owner, amount) in [("alice", 100), ("bob", 300)] {
+            let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(owner, amount)).unwrap();
+        }
+        // pooled vault doesn't get rewards
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(MOCK_CONTRACT_ADDR, 100)).unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("reward_token", &[]), fund(400, &AssetInfo::Native(String::from("ujunox")))).unwrap_err();
+        assert_eq!(err, ContractError::NoRewardRecipients { token: String::from("native:ujunox") });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("unknown_token", &[]), fund(400, &token)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("reward_token", &[]), fund(400, &token)).unwrap();
+
+        // deposit settles rewards of the old balance, and later rewards use the new balance
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive("alice", 100)).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("reward_token", &[]), fund(500, &token)).unwrap();
+
+        let pending = |deps: Deps, owner: &str| -> Vec<Asset> {
+            let msg = QueryMsg::PendingRewards { owner: Addr::unchecked(owner), vault_id: 0 };
+            let res: PendingRewardsResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.rewards
+        };
+        assert_eq!(pending(deps.as_ref(), "alice"), vec![Asset { info: reward_token.clone(), amount: Uint128::new(300) }]);
+        assert_eq!(pending(deps.as_ref(), "bob"), vec![Asset { info: reward_token.clone(), amount: Uint128::new(600) }]);
+        assert_eq!(pending(deps.as_ref(), MOCK_CONTRACT_ADDR), vec![]);
+
+        let claim = ExecuteMsg::ClaimRewards { vault_id: 0 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), claim.clone()).unwrap();
+        assert_eq!(res.messages[0].msg, reward_token.transfer_msg(&Addr::unchecked("alice"), Uint128::new(300)).unwrap());
+        assert_eq!(pending(deps.as_ref(), "alice"), vec![]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), claim).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // pool stays out of rewards as its coins change
+        let _res = execute(deps.as_mut(), mock_env(), mock_info(MOCK_CONTRACT_ADDR, &[]), receive(MOCK_CONTRACT_ADDR, 100)).unwrap();
+        assert_eq!(EXCLUDED_COLLECTED.load(deps.as_ref().storage, &token.key()).unwrap(), Uint128::new(200));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("reward_token", &[]), fund(500, &token)).unwrap();
+        assert_eq!(pending(deps.as_ref(), "alice"), vec![Asset { info: reward_token.clone(), amount: Uint128::new(200) }]);
+        assert_eq!(pending(deps.as_ref(), MOCK_CONTRACT_ADDR), vec![]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), ExecuteMsg::ClaimRewards { vault_id: 1 }).unwrap_err();
+        assert_eq!(err, ContractError::VaultNotFound {});
+    }
+
+    #[test]
+    fn unlock_order() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            early_withdraw_penalty: Some(EarlyWithdrawPenalty {
+                rate: Decimal::percent(10),
+                linear_decay: false,
+                destination: PenaltyDestination::Redistribute {},
+            }),
+            ..default_instantiate()
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateVault { label: None, lock_duration: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("tx_sender", &[]), msg).unwrap();
+
+        // deposits unlocked at 3600, 1800 and 600 in order of deposit
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(0);
+        for unlock in [3600, 1800, 600] {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(100),
+                msg: to_json_binary(&ReceiveMsg { unlock_at: Some(Expiration::AtTime(Timestamp::from_seconds(unlock))), ..receive_msg("tx_sender", 0) }).unwrap(),
+            });
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+        }
+        let key = ((&Addr::unchecked("tx_sender"), 0), "cw20:cosmos2contract");
+        let unlocks = |deps: Deps| UNLOCK_TIMES.prefix(key).keys(deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+        assert_eq!(unlocks(deps.as_ref()), vec![(600_000_000_000, 2), (1_800_000_000_000, 1), (3_600_000_000_000, 0)]);
+
+        // unlocked deposit is withdrawn without looking at locked deposits before it
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1200);
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(101) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsLocked { unlock_at: Expiration::AtTime(Timestamp::from_seconds(1800)) });
+        let msg = ExecuteMsg::Withdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(100) };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("tx_sender", &[]), msg).unwrap();
+        assert_eq!(unlocks(deps.as_ref()), vec![(1_800_000_000_000, 1), (3_600_000_000_000, 0)]);
+
+        // early withdraw takes locked coins unlocked earlier first
+        let msg = ExecuteMsg::EarlyWithdraw { vault_id: 0, token: AssetInfo::Cw20(Addr::unchecked(MOCK_CONTRACT_ADDR)), amount: Uint128::new(150) };
+        let res = execute(deps.as_mut(), env, mock_info("tx_sender", &[]), msg).unwrap();
+        assert_eq!(res.attributes.get(3).unwrap().value, "15");
+        assert_eq!(unlocks(deps.as_ref()), vec![(3_600_000_000_000, 0)]);
+        let ledger = LEDGERS.load(deps.as_ref().storage, (key.0, key.1, 0)).unwrap();
+        assert_eq!(ledger.coin_amount, Uint128::new(50));
     }
 }
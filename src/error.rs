@@ -1,13 +1,176 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_utils::Expiration;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Lock period is out of the allowed range")]
+    InvalidLockPeriod {},
+
+    #[error("Only one of lock_for, unlock_at and vesting can be set")]
+    ConflictingLockPeriod {},
+
+    #[error("Vesting schedule should be start_time <= cliff_time <= end_time")]
+    InvalidVestingSchedule {},
+
+    #[error("Insufficient funds: available {available}, requested {requested}")]
+    InsufficientFunds { available: Uint128, requested: Uint128 },
+
+    #[error("Funds are locked until {unlock_at}")]
+    FundsLocked { unlock_at: Expiration },
+
+    #[error("Vault already exists")]
+    VaultAlreadyExists {},
+
+    #[error("Vault not found")]
+    VaultNotFound {},
+
+    #[error("Invalid receive msg")]
+    InvalidReceiveMsg {},
+
+    #[error("Amount should be greater than zero")]
+    ZeroAmount {},
+
+    #[error("Withdraw is disabled while unbonding period is set. Use Unbond and Claim")]
+    UnbondingRequired {},
+
+    #[error("No matured claims")]
+    NothingToClaim {},
+
+    #[error("Early withdraw is not enabled")]
+    EarlyWithdrawDisabled {},
+
+    #[error("Penalty rate should be between 0 and 1")]
+    InvalidPenaltyRate {},
+
+    #[error("Token {token} is not whitelisted")]
+    TokenNotWhitelisted { token: String },
+
+    #[error("NFT not found in vault")]
+    NftNotFound {},
+
+    #[error("Vault can't be transferred to its owner")]
+    InvalidNewOwner {},
+
+    #[error("No pending vault transfer")]
+    NoPendingTransfer {},
+
+    #[error("Vault transfer is expired")]
+    TransferExpired {},
+
+    #[error("Another vault transfer to this address is pending")]
+    TransferAlreadyPending {},
+
+    #[error("Vault transfer doesn't allow merging")]
+    MergeNotAllowed {},
+
+    #[error("Cannot set allowance to own account")]
+    CannotSetOwnAccount {},
+
+    #[error("Invalid expiration value")]
+    InvalidExpiration {},
+
+    #[error("No allowance for this account")]
+    NoAllowance {},
+
+    #[error("Allowance is expired")]
+    AllowanceExpired {},
+
+    #[error("Insufficient allowance: allowance {allowance}, requested {requested}")]
+    InsufficientAllowance { allowance: Uint128, requested: Uint128 },
+
+    #[error("Threshold should be between 1 and total weight of members")]
+    InvalidThreshold {},
+
+    #[error("Proposal expiration should be within max voting period")]
+    WrongExpiration {},
+
+    #[error("Already voted on this proposal")]
+    AlreadyVoted {},
+
+    #[error("Proposal is not open")]
+    NotOpen {},
+
+    #[error("Proposal voting period has expired")]
+    ProposalExpired {},
+
+    #[error("Proposal voting period has not expired")]
+    NotExpired {},
+
+    #[error("Proposal must have passed and not yet been executed")]
+    WrongExecuteStatus {},
+
+    #[error("Cannot close completed or passed proposals")]
+    WrongCloseStatus {},
+
+    #[error("Beneficiary shares should be greater than zero and sum to 1")]
+    InvalidBeneficiaries {},
+
+    #[error("No beneficiaries for this vault")]
+    NoInheritance {},
+
+    #[error("Owner is active. Vault can be inherited at {claimable_at}")]
+    OwnerActive { claimable_at: Expiration },
+
+    #[error("Nothing left to inherit")]
+    NothingToInherit {},
+
+    #[error("Guardian threshold should be between 1 and number of guardians")]
+    InvalidGuardianThreshold {},
+
+    #[error("No pending recovery")]
+    NoPendingRecovery {},
+
+    #[error("Another recovery of this vault is pending")]
+    RecoveryAlreadyPending {},
+
+    #[error("Already approved this recovery")]
+    AlreadyApproved {},
+
+    #[error("Recovery is not ready")]
+    RecoveryNotReady {},
+
+    #[error("Recovery delay is over")]
+    RecoveryReady {},
+
+    #[error("No pending admin transfer")]
+    NoPendingAdmin {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Pause should pause at least one operation")]
+    InvalidPause {},
+
+    #[error("Contract is winding down")]
+    WindingDown {},
+
+    #[error("Contract is not winding down")]
+    NotWindingDown {},
+
+    #[error("Depositor is not allowed to deposit into this vault")]
+    DepositNotAllowed {},
+
+    #[error("Memo should be at most 256 bytes and reference between 1 and 64 bytes")]
+    InvalidMemo {},
+
+    #[error("Payment reference {reference} is already used")]
+    ReferenceAlreadyUsed { reference: String },
+
+    #[error("Pooled vault only accepts {token}")]
+    InvalidPoolToken { token: String },
+
+    #[error("No coins of {token} in vaults to reward")]
+    NoRewardRecipients { token: String },
+
+    #[error("Vault has more than {max} deposits, NFTs and claims to move")]
+    TooManyEntries { max: u64 },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
\ No newline at end of file
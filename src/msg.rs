@@ -1,39 +1,790 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Uint128, Addr};
-use crate::state::{Ledger};
+use cosmwasm_std::{Binary, Decimal, Uint128, Addr, Timestamp};
+use crate::state::{
+    Activity, AssetInfo, Beneficiary, Claim, Deposit, DepositPolicy, EarlyWithdrawPenalty, Guardians, Ledger, Member, MultisigAction, NftLedger, Recovery, Status, Vault, VaultTransfer,
+    VestingSchedule, Vote, WindDown,
+};
 use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Expiration};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub cw20_addr: String,
+    // whitelisted tokens which can be deposited
+    pub tokens: Vec<AssetInfo>,
+    // deposits can be withdrawn once this duration (time or block height) has passed
+    pub lock_duration: Duration,
+    // bounds for the lock period depositors can choose in ReceiveMsg
+    pub min_lock_duration: Option<Duration>,
+    pub max_lock_duration: Option<Duration>,
+    // if set, coins are withdrawn with Unbond and Claim instead of Withdraw
+    pub unbonding_period: Option<Duration>,
+    // if set, locked coins can be withdrawn with EarlyWithdraw by paying penalty
+    pub early_withdraw_penalty: Option<EarlyWithdrawPenalty>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    CreateVault(),
+    // create a new vault of sender. vault id is assigned in order of creation from 0
+    CreateVault {
+        label: Option<String>,
+        // default lock duration of deposits to this vault, within the bounds in Config
+        lock_duration: Option<Duration>,
+    },
+    // vault_id is 0 if not set, the first vault of sender
     Withdraw {
+        #[serde(default)]
+        vault_id: u64,
+        token: AssetInfo,
+        amount: Uint128,
+    },
+    // move coins out of vault to claims. they can be claimed after unbonding period
+    Unbond {
+        #[serde(default)]
+        vault_id: u64,
+        token: AssetInfo,
+        amount: Uint128,
+    },
+    // receive all matured claims
+    Claim {},
+    // settle rewards of vault and receive all settled rewards of sender
+    ClaimRewards {
+        #[serde(default)]
+        vault_id: u64,
+    },
+    // withdraw including locked coins. penalty is charged on locked coins
+    EarlyWithdraw {
+        #[serde(default)]
+        vault_id: u64,
+        token: AssetInfo,
         amount: Uint128,
     },
     // deposit. be executed when you send coin to this contract in CW20 contract
     Receive(Cw20ReceiveMsg),
+    // deposit native coins sent with this msg. each coin is recorded as a deposit
+    Deposit(ReceiveMsg),
+    // deposit NFT. be executed when you send NFT to this contract in CW721 contract. vesting can't be set
+    ReceiveNft(Cw721ReceiveMsg),
+    // withdraw NFT after its lock period
+    WithdrawNft {
+        #[serde(default)]
+        vault_id: u64,
+        collection: Addr,
+        token_id: String,
+    },
+    // propose to move sender vault to new owner. replaces the previous proposal for the vault
+    ProposeVaultTransfer {
+        #[serde(default)]
+        vault_id: u64,
+        new_owner: String,
+        expiry: Option<Expiration>,
+        // allow new owner to merge the vault into one of its vaults
+        #[serde(default)]
+        merge: bool,
+    },
+    // new owner takes over the vault with all its deposits and NFTs as a new vault,
+    // or merges them into its vault into_vault_id if merge is allowed
+    AcceptVaultTransfer {
+        into_vault_id: Option<u64>,
+    },
+    CancelVaultTransfer {
+        #[serde(default)]
+        vault_id: u64,
+    },
+    // choose who can deposit into sender vault. depositor list is kept when policy changes
+    SetDepositPolicy {
+        #[serde(default)]
+        vault_id: u64,
+        policy: DepositPolicy,
+    },
+    // update allowlist or denylist of sender vault
+    UpdateDepositors {
+        #[serde(default)]
+        vault_id: u64,
+        #[serde(default)]
+        add: Vec<String>,
+        #[serde(default)]
+        remove: Vec<String>,
+    },
+    // allow spender to withdraw unlocked coins of token from sender vault with WithdrawFrom.
+    // expires replaces the previous expiration if set
+    IncreaseVaultAllowance {
+        #[serde(default)]
+        vault_id: u64,
+        spender: String,
+        token: AssetInfo,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    DecreaseVaultAllowance {
+        #[serde(default)]
+        vault_id: u64,
+        spender: String,
+        token: AssetInfo,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    // withdraw from vault of owner within allowance of sender. coins are sent to recipient, sender if not set
+    WithdrawFrom {
+        owner: String,
+        #[serde(default)]
+        vault_id: u64,
+        token: AssetInfo,
+        amount: Uint128,
+        recipient: Option<String>,
+    },
+    // threshold of guardians can move sender vault to a new owner after delay.
+    // replaces previous guardians and pending recovery, and empty guardians removes them
+    SetGuardians {
+        #[serde(default)]
+        vault_id: u64,
+        guardians: Vec<String>,
+        threshold: u64,
+        delay: Duration,
+    },
+    // guardian starts recovery of vault to new owner, approving it
+    InitiateRecovery {
+        owner: String,
+        #[serde(default)]
+        vault_id: u64,
+        new_owner: String,
+    },
+    ApproveRecovery {
+        owner: String,
+        #[serde(default)]
+        vault_id: u64,
+    },
+    // owner cancels pending recovery of its vault
+    CancelRecovery {
+        #[serde(default)]
+        vault_id: u64,
+    },
+    // anyone moves vault to new owner as a new vault after delay
+    ExecuteRecovery {
+        owner: String,
+        #[serde(default)]
+        vault_id: u64,
+    },
+    // beneficiaries can claim their share of vault after sender is inactive for inactivity period.
+    // replaces previous beneficiaries, and empty beneficiaries removes them
+    SetBeneficiaries {
+        #[serde(default)]
+        vault_id: u64,
+        beneficiaries: Vec<Beneficiary>,
+        inactivity_period: Duration,
+    },
+    // reset inactivity timer of sender. any action of vault owner also resets it
+    Heartbeat {},
+    // beneficiary withdraws its share of unlocked coins of token after owner is inactive
+    ClaimInheritance {
+        owner: String,
+        #[serde(default)]
+        vault_id: u64,
+        token: AssetInfo,
+    },
+    // create vault owned by this contract and controlled by members.
+    // threshold is the yes weight required to pass a proposal
+    CreateMultisigVault {
+        label: Option<String>,
+        lock_duration: Option<Duration>,
+        members: Vec<Member>,
+        threshold: u64,
+        max_voting_period: Duration,
+    },
+    // create vault owned by this contract whose coins of token are shared by holders of its shares.
+    // deposit to it mints shares of depositor, locked by the lock of the deposit
+    CreatePooledVault {
+        label: Option<String>,
+        token: AssetInfo,
+        lock_duration: Option<Duration>,
+    },
+    // burn shares of pooled vault and withdraw coins for them. coins go to claims if unbonding period is set
+    Redeem {
+        vault_id: u64,
+        shares: Uint128,
+    },
+    // member proposes an action on multisig vault and votes yes. expires at latest, or after max voting period
+    Propose {
+        vault_id: u64,
+        title: String,
+        description: String,
+        action: MultisigAction,
+        latest: Option<Expiration>,
+    },
+    Vote {
+        proposal_id: u64,
+        vote: Vote,
+    },
+    // anyone can execute passed proposal
+    Execute {
+        proposal_id: u64,
+    },
+    // reject expired proposal which didn't pass
+    Close {
+        proposal_id: u64,
+    },
+    // only contract owner can update config. lock duration applies to new deposits
+    UpdateConfig(UpdateConfigMsg),
+    // admin proposes new admin, who becomes admin with AcceptAdmin. replaces the previous proposal
+    ProposeNewAdmin {
+        new_admin: String,
+    },
+    AcceptAdmin {},
+    // admin gives up admin permanently. config and tokens can't be changed anymore
+    RenounceAdmin {},
+    // admin sets or removes pauser
+    SetPauser {
+        pauser: Option<String>,
+    },
+    // admin or pauser pauses operations set to true, in addition to already paused ones.
+    // until replaces the automatic unpause time
+    Pause {
+        #[serde(default)]
+        deposits: bool,
+        #[serde(default)]
+        withdrawals: bool,
+        #[serde(default)]
+        vault_creation: bool,
+        until: Option<Expiration>,
+    },
+    // only admin can unpause all operations
+    Unpause {},
+    // admin starts wind-down after wind_down_delay. anyone can start it if contract is paused
+    // longer than max_pause_duration
+    StartWindDown {},
+    // admin cancels wind-down before it starts
+    CancelWindDown {},
+    // during wind-down, sender withdraws all coins and NFTs of its vaults regardless of locks
+    EmergencyExit {},
+    // only contract owner can add or remove whitelisted tokens and NFT collections
+    AddToken {
+        token: AssetInfo,
+    },
+    RemoveToken {
+        token: AssetInfo,
+    },
+    AddCollection {
+        collection: String,
+    },
+    RemoveCollection {
+        collection: String,
+    },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+// cw721 interface, same as cw721 crate
+#[cw_serde]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+#[cw_serde]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
+}
+
+#[cw_serde]
+pub struct UpdateConfigMsg {
+    pub lock_duration: Option<Duration>,
+    pub min_lock_duration: Option<Duration>,
+    pub max_lock_duration: Option<Duration>,
+    pub unbonding_period: Option<Duration>,
+    pub early_withdraw_penalty: Option<EarlyWithdrawPenalty>,
+    #[serde(default)]
+    pub wind_down_delay: Option<Duration>,
+    #[serde(default)]
+    pub max_pause_duration: Option<Duration>,
+    // optional settings to remove. applied before the values above
+    #[serde(default)]
+    pub unset: Vec<ConfigField>,
+}
+
+#[cw_serde]
+pub enum ConfigField {
+    MinLockDuration,
+    MaxLockDuration,
+    UnbondingPeriod,
+    EarlyWithdrawPenalty,
+    WindDownDelay,
+    MaxPauseDuration,
 }
 
 #[cw_serde]
 pub struct ReceiveMsg {
     pub vault_owner_addr: Addr,
+    // 0 if not set, the first vault of owner
+    #[serde(default)]
+    pub vault_id: u64,
+    // lock this deposit for the given duration. default is lock duration of vault, or in Config
+    pub lock_for: Option<Duration>,
+    // lock this deposit until the given time or height. can't be used with lock_for
+    pub unlock_at: Option<Expiration>,
+    // release this deposit linearly from start to end time. can't be used with lock_for, unlock_at
+    pub vesting: Option<VestingSchedule>,
+    // recorded with the deposit. can't be set for NFT
+    pub memo: Option<String>,
+    // unique payment reference, e.g. invoice id. Deposit of several native coins can't set it
+    pub reference: Option<String>,
+}
+
+// msg of Cw20ReceiveMsg which doesn't deposit to a vault
+#[cw_serde]
+pub enum ReceiveHookMsg {
+    // share sent cw20 tokens among vaults holding staked_token pro-rata to their collected coins.
+    // multisig and pooled vaults don't get rewards
+    FundRewards { staked_token: AssetInfo },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    // vault_id is 0 if not set, the first vault of owner
     #[returns(VaultResponse)]
-    GetVault { vault_owner_addr: Addr },
+    GetVault { vault_owner_addr: Addr, #[serde(default)] vault_id: u64, token: AssetInfo },
+    // collected coins of vault per token
+    #[returns(BalancesResponse)]
+    Balances { vault_owner_addr: Addr, #[serde(default)] vault_id: u64 },
+    // config with admin, pending admin, pauser, whitelisted tokens and NFT collections
+    #[returns(ConfigResponse)]
+    Config {},
+    // operations paused now
+    #[returns(PauseStatusResponse)]
+    PauseStatus {},
+    #[returns(WindDownStatusResponse)]
+    WindDownStatus {},
+    // whitelisted tokens
+    #[returns(TokensResponse)]
+    Tokens {},
+    #[returns(CollectionsResponse)]
+    Collections {},
+    // claims of address in order of unbond. start_after is claim id
+    #[returns(ClaimsResponse)]
+    Claims { address: Addr, start_after: Option<u64>, limit: Option<u32> },
+    // settled rewards of owner and rewards accrued in vault, which ClaimRewards of the vault sends
+    #[returns(PendingRewardsResponse)]
+    PendingRewards { owner: Addr, #[serde(default)] vault_id: u64 },
+    #[returns(EarlyWithdrawQuoteResponse)]
+    EarlyWithdrawQuote { vault_owner_addr: Addr, #[serde(default)] vault_id: u64, token: AssetInfo, amount: Uint128 },
+    // deposits of vault in order of deposit. start_after is ledger id
+    #[returns(LedgerEntriesResponse)]
+    LedgerEntries {
+        owner: Addr,
+        #[serde(default)]
+        vault_id: u64,
+        token: AssetInfo,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // deposit made with payment reference
+    #[returns(DepositInfo)]
+    DepositByReference { reference: String },
+    // deposits of depositor to all vaults in order of deposit
+    #[returns(DepositsResponse)]
+    DepositsByDepositor { depositor: Addr, start_after: Option<u64>, limit: Option<u32> },
+    // all vaults in order of owner address and vault id
+    #[returns(ListVaultsResponse)]
+    ListVaults { start_after: Option<VaultRef>, limit: Option<u32> },
+    // vaults of owner in order of vault id
+    #[returns(VaultsByOwnerResponse)]
+    VaultsByOwner { owner: Addr, start_after: Option<u64>, limit: Option<u32> },
+    // coins which can be withdrawn at at_time(now if not set) and when locked coins are released
+    #[returns(WithdrawableAmountResponse)]
+    WithdrawableAmount { owner: Addr, #[serde(default)] vault_id: u64, token: AssetInfo, at_time: Option<Timestamp>, limit: Option<u32> },
+    // deposits which Withdraw(or Unbond) of amount would take coins from now
+    #[returns(SimulateWithdrawResponse)]
+    SimulateWithdraw { owner: Addr, #[serde(default)] vault_id: u64, token: AssetInfo, amount: Uint128 },
+    // NFTs in vault in order of (collection, token id)
+    #[returns(NftsResponse)]
+    Nfts {
+        vault_owner_addr: Addr,
+        #[serde(default)]
+        vault_id: u64,
+        start_after: Option<NftId>,
+        limit: Option<u32>,
+    },
+    // proposed transfer of vault
+    #[returns(VaultTransferResponse)]
+    VaultTransfer { vault_owner_addr: Addr, #[serde(default)] vault_id: u64 },
+    #[returns(ProposalResponse)]
+    Proposal { proposal_id: u64 },
+    // proposals of all multisig vaults in order of id
+    #[returns(ProposalListResponse)]
+    ListProposals { start_after: Option<u64>, limit: Option<u32> },
+    // members of multisig vault in order of address
+    #[returns(VoterListResponse)]
+    Voters { vault_id: u64, start_after: Option<Addr>, limit: Option<u32> },
+    // coins of pooled vault, including redistributed coins not added yet, and its shares
+    #[returns(TotalAssetsResponse)]
+    TotalAssets { vault_id: u64 },
+    // shares for assets at the current share price, rounded down
+    #[returns(SharesResponse)]
+    ConvertToShares { vault_id: u64, assets: Uint128 },
+    // assets for shares at the current share price, rounded down
+    #[returns(AssetsResponse)]
+    ConvertToAssets { vault_id: u64, shares: Uint128 },
+    // shares deposit of assets would mint now. the same as ConvertToShares as pool has no fee
+    #[returns(SharesResponse)]
+    PreviewDeposit { vault_id: u64, assets: Uint128 },
+    // assets Redeem of shares would withdraw now. the same as ConvertToAssets as pool has no fee
+    #[returns(AssetsResponse)]
+    PreviewRedeem { vault_id: u64, shares: Uint128 },
+    // shares of holder in pooled vault and what they are worth
+    #[returns(PoolShareResponse)]
+    PoolShare { vault_id: u64, owner: Addr },
+    // guardians and pending recovery of vault
+    #[returns(RecoveryResponse)]
+    Recovery {
+        owner: Addr,
+        #[serde(default)]
+        vault_id: u64,
+    },
+    // beneficiaries of vault and what they claimed
+    #[returns(InheritanceResponse)]
+    Inheritance {
+        owner: Addr,
+        #[serde(default)]
+        vault_id: u64,
+    },
+    #[returns(LastActivityResponse)]
+    LastActivity { owner: Addr },
+    // deposit policy of vault and its depositor list in order of address
+    #[returns(DepositPolicyResponse)]
+    DepositPolicy {
+        owner: Addr,
+        #[serde(default)]
+        vault_id: u64,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    // allowances of vault in order of (spender, token)
+    #[returns(AllowancesResponse)]
+    Allowances {
+        owner: Addr,
+        #[serde(default)]
+        vault_id: u64,
+        start_after: Option<AllowanceId>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct VaultResponse {
     pub owner_addr: String,
+    pub vault_id: u64,
+    pub label: Option<String>,
+    pub token: AssetInfo,
+    // remaining coins in vault
     pub collected: Uint128,
-    pub ledger_list: Vec<Ledger>,
+    // vested(unlocked) coins not claimed yet, which can be withdrawn now.
+    // deposits are listed with LedgerEntries
+    pub vested: Uint128,
+    // coins already withdrawn from deposits of vault
+    pub claimed: Uint128,
+}
+
+#[cw_serde]
+pub struct ClaimsResponse {
+    // claims still in unbonding period
+    pub pending: Vec<ClaimInfo>,
+    // claims which can be received with Claim
+    pub matured: Vec<ClaimInfo>,
+}
+
+#[cw_serde]
+pub struct ClaimInfo {
+    pub id: u64,
+    pub claim: Claim,
+}
+
+#[cw_serde]
+pub struct PendingRewardsResponse {
+    pub rewards: Vec<Asset>,
+}
+
+#[cw_serde]
+pub struct EarlyWithdrawQuoteResponse {
+    // coins taken from vault
+    pub amount: Uint128,
+    pub penalty: Uint128,
+    // coins sent to vault owner
+    pub receive_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct LedgerEntry {
+    pub id: u64,
+    pub ledger: Ledger,
+}
+
+#[cw_serde]
+pub struct LedgerEntriesResponse {
+    pub entries: Vec<LedgerEntry>,
+}
+
+#[cw_serde]
+pub struct DepositInfo {
+    pub id: u64,
+    pub deposit: Deposit,
+}
+
+#[cw_serde]
+pub struct DepositsResponse {
+    pub deposits: Vec<DepositInfo>,
+}
+
+#[cw_serde]
+pub struct VaultSummary {
+    pub owner_addr: String,
+    pub vault_id: u64,
+    pub label: Option<String>,
+    // collected coins per token
+    pub balances: Vec<Asset>,
+}
+
+#[cw_serde]
+pub struct ListVaultsResponse {
+    pub vaults: Vec<VaultSummary>,
+}
+
+#[cw_serde]
+pub struct VaultRef {
+    pub owner: Addr,
+    pub vault_id: u64,
+}
+
+#[cw_serde]
+pub struct VaultsByOwnerResponse {
+    pub vaults: Vec<Vault>,
+}
+
+#[cw_serde]
+pub struct UnlockInfo {
+    pub unlock_at: Expiration,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct WithdrawableAmountResponse {
+    // coins which can be withdrawn
+    pub available: Uint128,
+    // coins still locked
+    pub locked: Uint128,
+    // locked coins in order of unlock time, from vesting deposits being released and
+    // the first limit locked deposits. the rest of locked coins is not listed
+    pub unlocks: Vec<UnlockInfo>,
+}
+
+#[cw_serde]
+pub struct LedgerWithdrawal {
+    pub id: u64,
+    // coins taken from this deposit
+    pub amount: Uint128,
+    // coins left in this deposit after withdraw
+    pub remaining: Uint128,
+}
+
+#[cw_serde]
+pub struct SimulateWithdrawResponse {
+    // coins which would be withdrawn. if less than requested amount, Withdraw fails
+    pub amount: Uint128,
+    pub entries: Vec<LedgerWithdrawal>,
+}
+
+#[cw_serde]
+pub struct TokenBalance {
+    pub token: AssetInfo,
+    pub collected: Uint128,
+    // coins which can be withdrawn now
+    pub vested: Uint128,
+}
+
+#[cw_serde]
+pub struct BalancesResponse {
+    pub balances: Vec<TokenBalance>,
+}
+
+#[cw_serde]
+pub struct TokensResponse {
+    pub tokens: Vec<AssetInfo>,
+}
+
+#[cw_serde]
+pub struct CollectionsResponse {
+    pub collections: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct NftId {
+    pub collection: Addr,
+    pub token_id: String,
+}
+
+#[cw_serde]
+pub struct NftInfo {
+    pub collection: Addr,
+    pub token_id: String,
+    pub ledger: NftLedger,
+}
+
+#[cw_serde]
+pub struct NftsResponse {
+    pub nfts: Vec<NftInfo>,
+}
+
+#[cw_serde]
+pub struct VaultTransferResponse {
+    pub transfer: Option<VaultTransfer>,
+}
+
+#[cw_serde]
+pub struct AllowanceId {
+    pub spender: Addr,
+    pub token: AssetInfo,
+}
+
+#[cw_serde]
+pub struct AllowanceInfo {
+    pub spender: Addr,
+    pub token: AssetInfo,
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct AllowancesResponse {
+    pub allowances: Vec<AllowanceInfo>,
+}
+
+#[cw_serde]
+pub enum ThresholdResponse {
+    AbsoluteCount { weight: u64, total_weight: u64 },
+}
+
+#[cw_serde]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub vault_id: u64,
+    pub title: String,
+    pub description: String,
+    pub action: MultisigAction,
+    pub status: Status,
+    pub expires: Expiration,
+    pub threshold: ThresholdResponse,
+    pub proposer: Addr,
+}
+
+#[cw_serde]
+pub struct ProposalListResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[cw_serde]
+pub struct VoterDetail {
+    pub addr: String,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub struct VoterListResponse {
+    pub voters: Vec<VoterDetail>,
+}
+
+#[cw_serde]
+pub struct BeneficiaryInfo {
+    pub addr: Addr,
+    pub share: Decimal,
+    // coins already claimed
+    pub claimed: Vec<Asset>,
+}
+
+#[cw_serde]
+pub struct InheritanceResponse {
+    pub beneficiaries: Vec<BeneficiaryInfo>,
+    pub inactivity_period: Duration,
+    pub last_activity: Option<Activity>,
+    pub claimable_at: Option<Expiration>,
+    pub claimable: bool,
+}
+
+#[cw_serde]
+pub struct LastActivityResponse {
+    pub last_activity: Option<Activity>,
+}
+
+#[cw_serde]
+pub struct RecoveryResponse {
+    pub guardians: Option<Guardians>,
+    pub recovery: Option<Recovery>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub owner: Option<Addr>,
+    pub pending_admin: Option<Addr>,
+    pub lock_duration: Duration,
+    pub min_lock_duration: Option<Duration>,
+    pub max_lock_duration: Option<Duration>,
+    pub unbonding_period: Option<Duration>,
+    pub early_withdraw_penalty: Option<EarlyWithdrawPenalty>,
+    pub wind_down_delay: Option<Duration>,
+    pub max_pause_duration: Option<Duration>,
+    pub tokens: Vec<AssetInfo>,
+    pub collections: Vec<Addr>,
+    pub pauser: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct PauseStatusResponse {
+    pub deposits: bool,
+    pub withdrawals: bool,
+    pub vault_creation: bool,
+    pub until: Option<Expiration>,
+    pub pauser: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct WindDownStatusResponse {
+    pub wind_down: Option<WindDown>,
+    pub active: bool,
+}
+
+#[cw_serde]
+pub struct TotalAssetsResponse {
+    pub token: AssetInfo,
+    pub total_assets: Uint128,
+    pub total_shares: Uint128,
+}
+
+#[cw_serde]
+pub struct SharesResponse {
+    pub shares: Uint128,
+}
+
+#[cw_serde]
+pub struct AssetsResponse {
+    pub assets: Uint128,
+}
+
+#[cw_serde]
+pub struct PoolShareResponse {
+    pub shares: Uint128,
+    pub assets: Uint128,
+    // None if holder has no shares
+    pub unlock_at: Option<Expiration>,
+}
+
+#[cw_serde]
+pub struct DepositPolicyResponse {
+    pub policy: DepositPolicy,
+    pub depositors: Vec<Addr>,
 }
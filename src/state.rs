@@ -1,26 +1,645 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128, Timestamp};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{coins, Addr, BankMsg, BlockInfo, CosmosMsg, Decimal, Empty, StdResult, Uint128, Timestamp};
+use cw20::{Cw20Contract, Cw20ExecuteMsg};
+use cw_storage_plus::{Item, Map, SnapshotMap, Strategy};
+use cw_utils::{Duration, Expiration};
+use std::fmt;
 
 #[cw_serde]
 pub struct Config {
-    pub owner: Addr,
-    pub cw20_addr: Addr,
+    // contract admin. None after admin is renounced
+    pub owner: Option<Addr>,
+    // how long each deposit stays locked after it is received
+    pub lock_duration: Duration,
+    // bounds for the lock period chosen by depositor in ReceiveMsg
+    pub min_lock_duration: Option<Duration>,
+    pub max_lock_duration: Option<Duration>,
+    // if set, coins are withdrawn with Unbond and Claim after this period instead of Withdraw
+    pub unbonding_period: Option<Duration>,
+    // if set, locked coins can be withdrawn with EarlyWithdraw by paying penalty
+    pub early_withdraw_penalty: Option<EarlyWithdrawPenalty>,
+    // wind-down starts this long after it is triggered. immediately if not set
+    pub wind_down_delay: Option<Duration>,
+    // if set, anyone can trigger wind-down when contract stays paused longer than this
+    pub max_pause_duration: Option<Duration>,
+}
+
+#[cw_serde]
+pub struct EarlyWithdrawPenalty {
+    // penalty rate on locked coins, between 0 and 1
+    pub rate: Decimal,
+    // if true, penalty decreases linearly until unlock time. height based locks always pay full rate
+    pub linear_decay: bool,
+    pub destination: PenaltyDestination,
+}
+
+#[cw_serde]
+pub enum PenaltyDestination {
+    // send penalty to treasury address
+    Treasury { address: Addr },
+    // share penalty to all vaults pro-rata to collected coins
+    Redistribute {},
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+// cw20 token or native bank denom
+#[cw_serde]
+pub enum AssetInfo {
+    Cw20(Addr),
+    Native(String),
+}
+
+impl AssetInfo {
+    // storage key of the asset
+    pub fn key(&self) -> String {
+        self.to_string()
+    }
+
+    // send amount of this asset from contract to recipient
+    pub fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        match self {
+            AssetInfo::Cw20(addr) => Cw20Contract(addr.clone()).call(Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            }),
+            AssetInfo::Native(denom) => Ok(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: coins(amount.u128(), denom),
+            }
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Cw20(addr) => write!(f, "cw20:{}", addr),
+            AssetInfo::Native(denom) => write!(f, "native:{}", denom),
+        }
+    }
+}
+
+// tokens which can be deposited, keyed by AssetInfo::key. removed tokens can still be withdrawn
+pub const TOKENS: Map<&str, AssetInfo> = Map::new("tokens");
+// NFT collections which can be deposited. NFTs of removed collections can still be withdrawn
+pub const COLLECTIONS: Map<&Addr, Empty> = Map::new("collections");
 
 #[cw_serde]
 pub struct Vault {
     pub owner_addr: Addr,
+    // id of vault among vaults of owner, assigned in order of creation from 0
+    pub vault_id: u64,
+    // unique among vaults of owner if set
+    pub label: Option<String>,
+    // default lock duration of deposits to this vault. lock duration in Config if not set
+    pub lock_duration: Option<Duration>,
+}
+
+// vault is identified by (owner, vault id)
+pub type VaultKey<'a> = (&'a Addr, u64);
+
+// coins of a token in vault
+#[cw_serde]
+pub struct VaultBalance {
+    pub owner_addr: Addr,
+    pub vault_id: u64,
+    pub token: AssetInfo,
     pub collected: Uint128,
-    pub ledger_list: Vec<Ledger>
+    // coins withdrawn from deposits so far, including penalty taken by early withdraw
+    #[serde(default)]
+    pub claimed: Uint128,
+    // id of the next deposit recorded in LEDGERS
+    pub next_ledger_id: u64,
+    // id of the oldest deposit not fully withdrawn. withdraw takes deposits in order of unlock time,
+    // so this is only used to skip withdrawn deposits when listing them in ledger_entries, like moving a vault
+    pub ledger_cursor: u64,
+    // PENALTY_INDEX of token when redistributed penalty was last added to this vault
+    pub penalty_index: Decimal,
 }
 
 #[cw_serde]
 pub struct Ledger {
+    // coin amount not withdrawn yet
+    pub coin_amount: Uint128,
+    // coin amount already withdrawn from this deposit
+    pub claimed_amount: Uint128,
+    pub receive_time: Timestamp,
+    // fixed at deposit time from ReceiveMsg or the lock duration in Config
+    pub unlock_at: Expiration,
+    // if set, deposit is released linearly instead of all at once
+    pub vesting: Option<VestingSchedule>,
+    // sender of the deposit. None for redistributed penalty and deposits migrated from the first release
+    pub depositor: Option<Addr>,
+    pub memo: Option<String>,
+    // payment reference from ReceiveMsg, unique among all deposits
+    pub reference: Option<String>,
+}
+
+impl Ledger {
+    // amount released from this deposit at given block, including claimed amount
+    pub fn vested_amount(&self, block: &BlockInfo) -> Uint128 {
+        let total = self.coin_amount + self.claimed_amount;
+        if !self.unlock_at.is_expired(block) {
+            return Uint128::zero();
+        }
+        match &self.vesting {
+            Some(vesting) => vesting.vested_amount(total, block.time),
+            None => total,
+        }
+    }
+
+    // amount which can be withdrawn from this deposit at given block
+    pub fn withdrawable_amount(&self, block: &BlockInfo) -> Uint128 {
+        self.vested_amount(block).saturating_sub(self.claimed_amount)
+    }
+
+    pub fn withdraw(&mut self, amount: Uint128) {
+        self.coin_amount -= amount;
+        self.claimed_amount += amount;
+    }
+
+    // when all coins of this deposit are released
+    pub fn fully_unlocked_at(&self) -> Expiration {
+        match &self.vesting {
+            Some(vesting) => Expiration::AtTime(vesting.end_time),
+            None => self.unlock_at,
+        }
+    }
+
+    // locked coins of this deposit at given block and when they are released.
+    // vesting deposit is released linearly between cliff time and end time, and reported as
+    // the amount released at cliff time and the rest at end time
+    pub fn pending_unlocks(&self, block: &BlockInfo) -> Vec<(Expiration, Uint128)> {
+        let locked = self.coin_amount - self.withdrawable_amount(block);
+        if locked.is_zero() {
+            return vec![];
+        }
+        match &self.vesting {
+            Some(vesting) if !self.unlock_at.is_expired(block) => {
+                let total = self.coin_amount + self.claimed_amount;
+                let at_cliff = vesting
+                    .vested_amount(total, vesting.cliff_time)
+                    .saturating_sub(self.claimed_amount)
+                    .min(locked);
+                vec![
+                    (Expiration::AtTime(vesting.cliff_time), at_cliff),
+                    (Expiration::AtTime(vesting.end_time), locked - at_cliff),
+                ]
+                .into_iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .collect()
+            }
+            Some(vesting) => vec![(Expiration::AtTime(vesting.end_time), locked)],
+            None => vec![(self.unlock_at, locked)],
+        }
+    }
+
+    // penalty for withdrawing amount of locked coins from this deposit at given block
+    pub fn early_withdraw_penalty(&self, penalty: &EarlyWithdrawPenalty, block: &BlockInfo, amount: Uint128) -> Uint128 {
+        let full = amount * penalty.rate;
+        if !penalty.linear_decay {
+            return full;
+        }
+        let (start, end) = match (&self.vesting, self.unlock_at) {
+            (Some(vesting), _) => (vesting.start_time, vesting.end_time),
+            (None, Expiration::AtTime(unlock_time)) => (self.receive_time, unlock_time),
+            _ => return full,
+        };
+        if block.time >= end {
+            return Uint128::zero();
+        }
+        if end <= start || block.time <= start {
+            return full;
+        }
+        full.multiply_ratio(end.seconds() - block.time.seconds(), end.seconds() - start.seconds())
+    }
+}
+
+#[cw_serde]
+pub struct VestingSchedule {
+    pub start_time: Timestamp,
+    // nothing is released before cliff time
+    pub cliff_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        self.start_time <= self.cliff_time
+            && self.cliff_time <= self.end_time
+            && self.start_time < self.end_time
+    }
+
+    // linearly released amount of total from start time to end time
+    pub fn vested_amount(&self, total: Uint128, time: Timestamp) -> Uint128 {
+        if time < self.cliff_time {
+            return Uint128::zero();
+        }
+        if time >= self.end_time {
+            return total;
+        }
+        total.multiply_ratio(
+            time.seconds() - self.start_time.seconds(),
+            self.end_time.seconds() - self.start_time.seconds(),
+        )
+    }
+}
+
+pub const VAULTS: Map<VaultKey, Vault> = Map::new("vaults");
+// id of the next vault created by owner
+pub const NEXT_VAULT_ID: Map<&Addr, u64> = Map::new("next_vault_id");
+
+#[cw_serde]
+pub struct VaultTransfer {
+    pub owner: Addr,
+    pub vault_id: u64,
+    pub new_owner: Addr,
+    // transfer can't be accepted after expiry
+    pub expiry: Option<Expiration>,
+    // if true, new owner can merge the vault into one of its vaults
+    pub merge: bool,
+}
+
+// proposed vault transfers keyed by (vault owner, vault id)
+pub const PENDING_TRANSFERS: Map<VaultKey, VaultTransfer> = Map::new("pending_transfer");
+// vault of proposed transfer keyed by new owner
+pub const INCOMING_TRANSFERS: Map<&Addr, (Addr, u64)> = Map::new("incoming_transfer");
+// balance of vault keyed by (vault, token key)
+pub const BALANCES: Map<(VaultKey, &str), VaultBalance> = Map::new("balance");
+// deposits of vault keyed by (vault, token key, ledger id). ledger id increases in order of deposit
+pub const LEDGERS: Map<(VaultKey, &str, u64), Ledger> = Map::new("ledger");
+// ledger ids keyed by (vault, token key, (unlock time in nanos, ledger id)) and by unlock height,
+// so withdrawals find unlocked deposits without iterating locked ones.
+// vesting deposit is kept at its cliff time, and deposit locked forever after all unlock times
+pub type UnlockIndex<'a> = Map<'a, (VaultKey<'a>, &'a str, (u64, u64)), ()>;
+pub const UNLOCK_TIMES: UnlockIndex = Map::new("unlock_time");
+pub const UNLOCK_HEIGHTS: UnlockIndex = Map::new("unlock_height");
+
+// deposit as received. kept after its coins are withdrawn or the vault is transferred,
+// so payments can be looked up by depositor and reference
+#[cw_serde]
+pub struct Deposit {
+    pub depositor: Addr,
+    // vault the deposit was made to
+    pub owner_addr: Addr,
+    pub vault_id: u64,
+    pub token: AssetInfo,
+    pub amount: Uint128,
+    pub memo: Option<String>,
+    pub reference: Option<String>,
+    pub receive_time: Timestamp,
+}
+
+pub const DEPOSIT_COUNT: Item<u64> = Item::new("deposit_count");
+// deposits by (depositor, deposit id)
+pub const DEPOSITS: Map<(&Addr, u64), Deposit> = Map::new("deposits");
+// payment reference to the deposit which used it
+pub const REFERENCES: Map<&str, (Addr, u64)> = Map::new("reference");
+
+// guardians can jointly move vault to a new owner after delay, in case owner loses keys
+#[cw_serde]
+pub struct Guardians {
+    pub guardians: Vec<Addr>,
+    pub threshold: u64,
+    pub delay: Duration,
+}
+
+pub const GUARDIANS: Map<VaultKey, Guardians> = Map::new("guardians");
+
+#[cw_serde]
+pub struct Recovery {
+    pub new_owner: Addr,
+    pub approvals: Vec<Addr>,
+    // set when approvals reach threshold. owner can cancel until it expires
+    pub ready_at: Option<Expiration>,
+}
+
+pub const RECOVERIES: Map<VaultKey, Recovery> = Map::new("recovery");
+
+#[cw_serde]
+pub struct Beneficiary {
+    pub addr: Addr,
+    pub share: Decimal,
+}
+
+// beneficiaries can claim their share of vault once owner is inactive for inactivity period
+#[cw_serde]
+pub struct Inheritance {
+    pub beneficiaries: Vec<Beneficiary>,
+    pub inactivity_period: Duration,
+}
+
+pub const INHERITANCES: Map<VaultKey, Inheritance> = Map::new("inheritance");
+// coins each beneficiary claimed keyed by (vault, beneficiary, token key)
+pub const INHERITED: Map<(VaultKey, &Addr, &str), Uint128> = Map::new("inherited");
+
+// block of the last owner action
+#[cw_serde]
+pub struct Activity {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+impl Activity {
+    pub fn expires(&self, period: &Duration) -> Expiration {
+        match period {
+            Duration::Height(height) => Expiration::AtHeight(self.height + height),
+            Duration::Time(time) => Expiration::AtTime(self.time.plus_seconds(*time)),
+        }
+    }
+}
+
+pub const LAST_ACTIVITY: Map<&Addr, Activity> = Map::new("last_activity");
+
+// paused operations. pause ends automatically at until if set. only admin can make it end earlier
+#[cw_serde]
+#[derive(Default)]
+pub struct Pause {
+    pub deposits: bool,
+    pub withdrawals: bool,
+    pub vault_creation: bool,
+    pub until: Option<Expiration>,
+    // block when contract was paused
+    pub paused_at: Option<Activity>,
+}
+
+impl Pause {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        !self.until.is_some_and(|until| until.is_expired(block))
+    }
+}
+
+pub const PAUSE: Item<Pause> = Item::new("pause");
+// can pause, but not unpause, besides admin
+pub const PAUSER: Item<Addr> = Item::new("pauser");
+
+// contract is retired from starts_at. deposits are refused and locks don't apply to withdrawals
+#[cw_serde]
+pub struct WindDown {
+    pub starts_at: Expiration,
+    pub triggered_by: Addr,
+}
+
+impl WindDown {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        self.starts_at.is_expired(block)
+    }
+}
+
+pub const WIND_DOWN: Item<WindDown> = Item::new("wind_down");
+
+// new admin proposed by admin, until accepted
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+
+// multisig vault is owned by this contract and controlled by proposals of its members
+#[cw_serde]
+pub struct Multisig {
+    // yes weight required to pass a proposal
+    pub threshold: u64,
+    pub total_weight: u64,
+    pub max_voting_period: Duration,
+}
+
+// multisig config keyed by vault id of vault owned by this contract
+pub const MULTISIGS: Map<u64, Multisig> = Map::new("multisig");
+// member weights of multisig vault keyed by (vault id, member). snapshot is used for voting weight
+pub const MEMBERS: SnapshotMap<(u64, &Addr), u64> = SnapshotMap::new(
+    "members",
+    "members__checkpoints",
+    "members__changelog",
+    Strategy::EveryBlock,
+);
+
+#[cw_serde]
+pub struct Member {
+    pub addr: Addr,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub enum MultisigAction {
+    // withdraw unlocked coins from vault in the same order as Withdraw
+    Withdraw { token: AssetInfo, amount: Uint128, recipient: Addr },
+    // members with weight 0 are removed
+    UpdateMembers { add: Vec<Member>, remove: Vec<Addr> },
+    UpdateThreshold { threshold: u64 },
+}
+
+#[cw_serde]
+pub enum Status {
+    Open,
+    Rejected,
+    Passed,
+    Executed,
+}
+
+#[cw_serde]
+pub enum Vote {
+    Yes,
+    No,
+    Abstain,
+    Veto,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Votes {
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub veto: u64,
+}
+
+impl Votes {
+    pub fn add_vote(&mut self, vote: Vote, weight: u64) {
+        match vote {
+            Vote::Yes => self.yes += weight,
+            Vote::No => self.no += weight,
+            Vote::Abstain => self.abstain += weight,
+            Vote::Veto => self.veto += weight,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Proposal {
+    pub vault_id: u64,
+    pub title: String,
+    pub description: String,
+    pub action: MultisigAction,
+    pub proposer: Addr,
+    // voting weight is taken from members at this height
+    pub start_height: u64,
+    pub expires: Expiration,
+    // threshold and total weight when proposal was created
+    pub threshold: u64,
+    pub total_weight: u64,
+    pub status: Status,
+    pub votes: Votes,
+}
+
+impl Proposal {
+    // status with expiration applied
+    pub fn current_status(&self, block: &BlockInfo) -> Status {
+        let mut status = self.status.clone();
+        if status == Status::Open && self.is_passed() {
+            status = Status::Passed;
+        }
+        if status == Status::Open && (self.is_rejected() || self.expires.is_expired(block)) {
+            status = Status::Rejected;
+        }
+        status
+    }
+
+    pub fn update_status(&mut self, block: &BlockInfo) {
+        self.status = self.current_status(block);
+    }
+
+    pub fn is_passed(&self) -> bool {
+        self.votes.yes >= self.threshold
+    }
+
+    // remaining weight can't reach threshold
+    pub fn is_rejected(&self) -> bool {
+        let opposed = self.votes.no + self.votes.abstain + self.votes.veto;
+        self.total_weight.saturating_sub(opposed) < self.threshold
+    }
+}
+
+#[cw_serde]
+pub struct Ballot {
+    pub weight: u64,
+    pub vote: Vote,
+}
+
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+// votes keyed by (proposal id, voter)
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+
+// pooled vault is owned by this contract and holds coins of one token for holders of its shares.
+// coins redistributed to the vault raise the share price for every holder
+#[cw_serde]
+pub struct Pool {
+    pub token: AssetInfo,
+    pub total_shares: Uint128,
+}
+
+// virtual shares and assets added to the pool when converting. they keep the price of the first shares
+// from being inflated by coins added without shares, at a loss for whoever tries it
+pub const POOL_VIRTUAL_SHARES: Uint128 = Uint128::new(1_000_000);
+pub const POOL_VIRTUAL_ASSETS: Uint128 = Uint128::new(1);
+
+impl Pool {
+    // shares for assets, rounded down in favor of the pool
+    pub fn to_shares(&self, assets: Uint128, total_assets: Uint128) -> Uint128 {
+        assets.multiply_ratio(self.total_shares + POOL_VIRTUAL_SHARES, total_assets + POOL_VIRTUAL_ASSETS)
+    }
+
+    // assets for shares, rounded down in favor of the pool
+    pub fn to_assets(&self, shares: Uint128, total_assets: Uint128) -> Uint128 {
+        shares.multiply_ratio(total_assets + POOL_VIRTUAL_ASSETS, self.total_shares + POOL_VIRTUAL_SHARES)
+    }
+}
+
+// pool keyed by vault id of vault owned by this contract
+pub const POOLS: Map<u64, Pool> = Map::new("pool");
+
+// coins of pooled vault are always unlocked, and the lock of deposits applies to shares of depositor
+#[cw_serde]
+pub struct PoolShare {
+    pub shares: Uint128,
+    // the latest unlock time of deposits of holder
+    pub unlock_at: Expiration,
+}
+
+pub const POOL_SHARES: Map<(u64, &Addr), PoolShare> = Map::new("pool_shares");
+
+// who can deposit into vault besides its owner
+#[cw_serde]
+#[derive(Default)]
+pub enum DepositPolicy {
+    #[default]
+    Open,
+    // only depositors in the list
+    Allowlist,
+    // anyone except depositors in the list
+    Denylist,
+}
+
+pub const DEPOSIT_POLICIES: Map<VaultKey, DepositPolicy> = Map::new("deposit_policy");
+// allowlist or denylist of vault depending on its policy, keyed by (vault, depositor)
+pub const DEPOSITORS: Map<(VaultKey, &Addr), Empty> = Map::new("depositors");
+
+// coins spender can withdraw from vault, like cw20 allowance
+#[cw_serde]
+pub struct Allowance {
+    pub token: AssetInfo,
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+// allowances keyed by (vault, spender, token key)
+pub const ALLOWANCES: Map<(VaultKey, &Addr, &str), Allowance> = Map::new("allowance");
+
+#[cw_serde]
+pub struct NftLedger {
+    pub receive_time: Timestamp,
+    // fixed at deposit time from ReceiveMsg or the lock duration in Config
+    pub unlock_at: Expiration,
+}
+
+// NFTs in vault keyed by (vault, collection, token id)
+pub const NFTS: Map<(VaultKey, &Addr, &str), NftLedger> = Map::new("nft");
+
+#[cw_serde]
+pub struct Claim {
+    pub token: AssetInfo,
+    pub amount: Uint128,
+    pub release_at: Expiration,
+}
+
+pub const CLAIM_COUNT: Item<u64> = Item::new("claim_count");
+// unbonded coins waiting to be claimed by (vault owner, claim id), from all vaults of owner
+pub const CLAIMS: Map<(&Addr, u64), Claim> = Map::new("claims");
+
+// sum of collected coins of all vaults per token, which redistributed penalty is shared by
+pub const TOTAL_COLLECTED: Map<&str, Uint128> = Map::new("total_collected");
+// redistributed penalty per collected coin per token, accumulated
+pub const PENALTY_INDEX: Map<&str, Decimal> = Map::new("penalty_index");
+
+// address of this contract, owner of multisig and pooled vaults
+pub const CONTRACT_ADDR: Item<Addr> = Item::new("contract_addr");
+// sum of collected coins of vaults owned by this contract per token, which don't earn rewards
+pub const EXCLUDED_COLLECTED: Map<&str, Uint128> = Map::new("excluded_collected");
+// rewards per collected coin, accumulated, keyed by (staked token, cw20 reward token)
+pub const REWARD_INDEX: Map<(&str, &Addr), Decimal> = Map::new("reward_index");
+// REWARD_INDEX when rewards of vault were last settled, keyed by (vault, staked token, reward token)
+pub const REWARD_CHECKPOINTS: Map<(VaultKey, &str, &Addr), Decimal> = Map::new("reward_checkpoint");
+// settled rewards not claimed yet, keyed by (vault owner, reward token)
+pub const REWARDS: Map<(&Addr, &Addr), Uint128> = Map::new("rewards");
+
+// storage of the first single token release, only read by migrate
+#[cw_serde]
+pub struct LegacyConfig {
+    pub owner: Addr,
+    pub cw20_addr: Addr,
+}
+
+#[cw_serde]
+pub struct LegacyVault {
+    pub owner_addr: Addr,
+    pub collected: Uint128,
+    pub ledger_list: Vec<LegacyLedger>,
+}
+
+// deposits were locked for 60 seconds after receive time
+#[cw_serde]
+pub struct LegacyLedger {
     pub coin_amount: Uint128,
     pub receive_time: Timestamp,
 }
 
-pub const VAULTS: Map<Addr, Vault> = Map::new("vault");
\ No newline at end of file
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const LEGACY_VAULTS: Map<Addr, LegacyVault> = Map::new("vault");